@@ -0,0 +1,20 @@
+//! cargo fuzz run ias_report
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use epid_verifier_lotto::intelstructs::{ClaimStruct, IASReport};
+use epid_verifier_lotto::state::ClaimPolicy;
+use epid_verifier_lotto::verify::verify_report;
+
+fuzz_target!(|data: &[u8]| {
+    // Whatever JSON gets past deserialization goes through every check a claim does without panicking. A report
+    // that passes them all has to carry Intel's signature, which a fuzzer can only have copied from a real one.
+    if let Ok(report) = serde_json::from_slice::<IASReport>(data) {
+        let claim = ClaimStruct::new(vec![], String::new());
+        let verification = verify_report(&report, &claim, &ClaimPolicy::default());
+        if verification.first_failure().is_none() {
+            assert!(verification.checks.iter().any(|check| check.check == "signature" && check.passed));
+        }
+    }
+});
@@ -0,0 +1,17 @@
+//! cargo fuzz run platform_info_blob
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use epid_verifier_lotto::platforminfoblob::{convert_platform_info_blob, convert_platform_info_hex};
+
+fuzz_target!(|data: &[u8]| {
+    // Only a blob of the right size parses
+    assert_eq!(convert_platform_info_blob(data).is_ok(), data.len() == 105);
+
+    if let Ok(blob_hex) = std::str::from_utf8(data) {
+        if let Ok(pib) = convert_platform_info_hex(blob_hex) {
+            let _ = (pib.group_id(), pib.epid_group_flag_names(), pib.tcb_evaluation_flag_names(), pib.pse_evaluation_flag_names());
+        }
+    }
+});
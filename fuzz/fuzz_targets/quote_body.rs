@@ -0,0 +1,24 @@
+//! cargo fuzz run quote_body
+#![no_main]
+
+use cosmwasm_std::Binary;
+use libfuzzer_sys::fuzz_target;
+
+use epid_verifier_lotto::platforminfoblob::{decode_quote_body, get_mrenclave_from_quote_body, get_payload_from_quote_body};
+
+fn parse(quote_body: &[u8]) {
+    assert_eq!(decode_quote_body(quote_body).is_ok(), quote_body.len() == 432);
+    assert_eq!(get_payload_from_quote_body(quote_body).is_ok(), quote_body.len() >= 432);
+    assert_eq!(get_mrenclave_from_quote_body(quote_body).is_ok(), quote_body.len() >= 144);
+}
+
+fuzz_target!(|data: &[u8]| {
+    parse(data);
+
+    // The way it is submitted, as base64 in the report
+    if let Ok(encoded) = std::str::from_utf8(data) {
+        if let Ok(quote_body) = Binary::from_base64(encoded) {
+            parse(&quote_body);
+        }
+    }
+});
@@ -0,0 +1,20 @@
+//! cargo fuzz run rsa_verify
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sha2::{Digest, Sha256};
+
+use epid_verifier_lotto::intelstructs::{IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS, verify_pkcs1v15_sha256};
+
+fuzz_target!(|data: &[u8]| {
+    // Against the key IAS signs reports with, with the input as the signature of its own hash
+    assert!(!verify_pkcs1v15_sha256(data, IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS, &Sha256::digest(data)));
+
+    // And against a key of the input's making, the first two bytes say how long the exponent and modulus are
+    if let [exponent_length, modulus_length, rest @ ..] = data {
+        let (exponent, rest) = rest.split_at((*exponent_length as usize % 5).min(rest.len()));
+        let (modulus, rest) = rest.split_at((*modulus_length as usize).min(rest.len()));
+        let (signature, digest) = rest.split_at(modulus.len().min(rest.len()));
+        let _ = verify_pkcs1v15_sha256(signature, exponent, modulus, digest);
+    }
+});
@@ -0,0 +1,66 @@
+//! Prints what an enclave has to put in its report data to claim for an address, and the claim to submit with the
+//! report it gets back from IAS.
+//!
+//! Usage: claim-payload <bech32 address> <message> [--json]
+
+use std::process::ExitCode;
+
+use serde::Serialize;
+
+use epid_verifier_lotto::payload::build_claim_payload;
+
+const USAGE: &str = "Usage: claim-payload <bech32 address> <message> [--json]";
+
+#[derive(Serialize)]
+struct Output {
+    report_data_hex: String,
+    report_data_base64: String,
+    /// The `to_claim` of the `ClaimGEID` message
+    claim: serde_json::Value
+}
+
+fn main() -> ExitCode {
+    let mut json = false;
+    let mut positional = vec![];
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let [address, message] = match <[String; 2]>::try_from(positional) {
+        Ok(args) => args,
+        Err(_) => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let payload = match build_claim_payload(&address, &message) {
+        Ok(payload) => payload,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if json {
+        let output = Output {
+            report_data_hex: payload.report_data_hex(),
+            report_data_base64: payload.report_data_base64(),
+            claim: serde_json::to_value(&payload.claim).expect("A ClaimStruct always serializes"),
+        };
+        println!("{}", serde_json::to_string_pretty(&output).expect("The output always serializes"));
+    } else {
+        println!("report data (hex):    {}", payload.report_data_hex());
+        println!("report data (base64): {}", payload.report_data_base64());
+        println!("claim:                {}", payload.claim_json());
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,425 @@
+//! Checks an IAS report and claim offline, the same way the contract does when claiming, so operators can find
+//! out why a report is refused before they submit it.
+//!
+//! Usage: epid-verify <report.json> <claim.json> [--cert-chain <chain.pem>] [--policy <policy.json>] [--json]
+//!
+//! `report.json` is the `report` that is submitted with a claim, the IAS response body along with its `reportsig`.
+//! Signatures are checked against the key the contract checks them against, Intel's report signing key. A cert chain,
+//! e.g. the one IAS sends along in `X-IASReport-Signing-Certificate`, is checked up to Intel's root CA and has to
+//! vouch for that same key, so the verdict is always the contract's. The chain's validity dates aren't checked. The
+//! policy is a campaign's claim policy as the `PotStats` query returns it, every report is accepted when there is none.
+
+use std::fs;
+use std::process::ExitCode;
+
+use cosmwasm_std::{Binary, ensure, MemoryStorage, StdError, StdResult};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use epid_verifier_lotto::intelstructs::{ClaimStruct, IASReport, INTEL_ROOT_EXPONENT, INTEL_ROOT_MODULUS, verify_pkcs1v15_sha256};
+use epid_verifier_lotto::msg::group_id_hex;
+use epid_verifier_lotto::platforminfoblob::{decode_quote_body, platform_info_blob, QuoteBody};
+use epid_verifier_lotto::state::{ClaimPolicy, load_signing_key, SigningKey};
+use epid_verifier_lotto::verify::{ReportCheck, verify_report_with_key};
+
+const USAGE: &str = "Usage: epid-verify <report.json> <claim.json> [--cert-chain <chain.pem>] [--policy <policy.json>] [--json]";
+
+struct Args {
+    report: String,
+    claim: String,
+    cert_chain: Option<String>,
+    policy: Option<String>,
+    json: bool
+}
+
+/// Everything we found out about the report
+#[derive(Serialize)]
+struct Breakdown {
+    report_id: String,
+    timestamp: String,
+    quote_status: String,
+    advisory_ids: Vec<String>,
+    /// Where the key the signature was checked against came from
+    signing_key: String,
+    signature_valid: bool,
+    payload_matches: bool,
+    quote_body: Option<QuoteBody>,
+    platform_info: Option<PlatformInfo>,
+    /// Every check a claim goes through that doesn't depend on the campaign's state, in the order they are run
+    checks: Vec<ReportCheck>,
+    /// Whether the policy accepts the report
+    accepted: bool
+}
+
+#[derive(Serialize)]
+struct PlatformInfo {
+    gid: u32,
+    gid_hex: String,
+    cpusvn_up_to_date: bool,
+    epid_group_flags: Vec<&'static str>,
+    tcb_evaluation_flags: Vec<&'static str>,
+    pse_evaluation_flags: Vec<&'static str>
+}
+
+impl From<platform_info_blob> for PlatformInfo {
+    fn from(pib: platform_info_blob) -> Self {
+        PlatformInfo {
+            gid: pib.group_id(),
+            gid_hex: group_id_hex(pib.group_id()),
+            cpusvn_up_to_date: pib.cpusvn_is_up_to_date(),
+            epid_group_flags: pib.epid_group_flag_names(),
+            tcb_evaluation_flags: pib.tcb_evaluation_flag_names(),
+            pse_evaluation_flags: pib.pse_evaluation_flag_names(),
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut positional = vec![];
+    let mut cert_chain = None;
+    let mut policy = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--cert-chain" => cert_chain = Some(args.next().ok_or("--cert-chain needs a file")?),
+            "--policy" => policy = Some(args.next().ok_or("--policy needs a file")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}\n{}", flag, USAGE)),
+            _ => positional.push(arg),
+        }
+    }
+
+    match <[String; 2]>::try_from(positional) {
+        Ok([report, claim]) => Ok(Args { report, claim, cert_chain, policy, json }),
+        Err(_) => Err(USAGE.to_string()),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<T, String> {
+    let contents = fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))?;
+    serde_json::from_str(&contents).map_err(|error| format!("{} isn't what we expected: {}", path, error))
+}
+
+/// Checks that every certificate of a PEM cert chain, leaf first, is signed by the one after it and that the last one
+/// is signed by `root`, and reads the leaf's RSA public key out as its modulus and exponent. IAS hands the chain out
+/// percent encoded, so that is decoded first.
+fn verify_cert_chain(chain: &str, root: (&[u8], &[u8])) -> StdResult<(Vec<u8>, Vec<u8>)> {
+    let chain = percent_decode(chain);
+    let certificates = chain.split("-----BEGIN CERTIFICATE-----").skip(1)
+        .map(|rest| rest.split("-----END CERTIFICATE-----").next().unwrap_or(rest))
+        .map(|pem| Binary::from_base64(&pem.split_whitespace().collect::<String>()).map(Vec::from))
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    ensure!(!certificates.is_empty(), StdError::generic_err("There is no certificate in the chain"));
+
+    for (position, certificate) in certificates.iter().enumerate() {
+        // The root may come last, in which case it's checked against itself
+        let (issuer, (modulus, exponent)) = match certificates.get(position + 1) {
+            Some(next) => ("the certificate after it", rsa_public_key_from_der(next)?),
+            None => ("the root CA", (root.0.to_vec(), root.1.to_vec())),
+        };
+        ensure!(
+            certificate_is_signed_by(certificate, &modulus, &exponent)?,
+            StdError::generic_err(format!("Certificate {} of the chain isn't signed by {}", position + 1, issuer))
+        );
+    }
+
+    rsa_public_key_from_der(&certificates[0])
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The DER encoding of the rsaEncryption object identifier, 1.2.840.113549.1.1.1
+const RSA_ENCRYPTION_OID: &[u8] = &[0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+
+/// Finds the certificate's subject public key, which follows the rsaEncryption algorithm identifier as a bit string
+/// holding `SEQUENCE { modulus INTEGER, publicExponent INTEGER }`. The signature algorithm of an RSA signed
+/// certificate is a different identifier, so the first match is the key's.
+fn rsa_public_key_from_der(der: &[u8]) -> StdResult<(Vec<u8>, Vec<u8>)> {
+    let not_rsa = || StdError::generic_err("The certificate doesn't hold an RSA key");
+
+    let oid_at = der.windows(RSA_ENCRYPTION_OID.len()).position(|window| window == RSA_ENCRYPTION_OID).ok_or_else(not_rsa)?;
+    let mut rest = &der[oid_at + RSA_ENCRYPTION_OID.len()..];
+
+    // The algorithm's parameters are NULL
+    if rest.starts_with(&[0x05, 0x00]) {
+        rest = &rest[2..];
+    }
+    let (bit_string, _) = der_element(rest, 0x03).ok_or_else(not_rsa)?;
+    // The bit string starts with the number of unused bits, which is none
+    let (key, _) = der_element(bit_string.get(1..).ok_or_else(not_rsa)?, 0x30).ok_or_else(not_rsa)?;
+    let (modulus, key) = der_element(key, 0x02).ok_or_else(not_rsa)?;
+    let (exponent, _) = der_element(key, 0x02).ok_or_else(not_rsa)?;
+
+    // A positive integer gets a leading zero when its top bit is set
+    let strip = |integer: &[u8]| integer.iter().skip_while(|byte| **byte == 0).copied().collect::<Vec<u8>>();
+    Ok((strip(modulus), strip(exponent)))
+}
+
+/// The DER encoding of the sha256WithRSAEncryption object identifier, 1.2.840.113549.1.1.11
+const SHA256_WITH_RSA_OID: &[u8] = &[0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0B];
+
+/// Whether the key made the certificate's signature. A certificate is `SEQUENCE { tbsCertificate, signatureAlgorithm,
+/// signatureValue BIT STRING }` and the signature is over the DER of the `tbsCertificate`, header included.
+fn certificate_is_signed_by(der: &[u8], modulus: &[u8], exponent: &[u8]) -> StdResult<bool> {
+    let malformed = || StdError::generic_err("A certificate of the chain doesn't decode");
+
+    let (certificate, _) = der_element(der, 0x30).ok_or_else(malformed)?;
+    let (_, rest) = der_element(certificate, 0x30).ok_or_else(malformed)?;
+    let signed = &certificate[..certificate.len() - rest.len()];
+    let (algorithm, rest) = der_element(rest, 0x30).ok_or_else(malformed)?;
+    ensure!(algorithm.starts_with(SHA256_WITH_RSA_OID), StdError::generic_err("A certificate of the chain isn't signed with sha256WithRSAEncryption"));
+    // The bit string starts with the number of unused bits, which is none
+    let (signature, _) = der_element(rest, 0x03).ok_or_else(malformed)?;
+
+    Ok(verify_pkcs1v15_sha256(signature.get(1..).ok_or_else(malformed)?, exponent, modulus, &Sha256::digest(signed)))
+}
+
+/// Splits the contents of a DER element with the given tag off the front of `input`
+fn der_element(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    if *input.first()? != tag {
+        return None;
+    }
+
+    let first_length_byte = *input.get(1)?;
+    let (length, header) = if first_length_byte < 0x80 {
+        (first_length_byte as usize, 2)
+    } else {
+        let length_bytes = (first_length_byte & 0x7F) as usize;
+        if length_bytes > std::mem::size_of::<usize>() {
+            return None;
+        }
+        let length = input.get(2..2 + length_bytes)?.iter().fold(0usize, |length, byte| (length << 8) | *byte as usize);
+        (length, 2 + length_bytes)
+    };
+
+    let end = header.checked_add(length)?;
+    Some((input.get(header..end)?, &input[end..]))
+}
+
+fn breakdown(report: &IASReport, claim: &ClaimStruct, policy: &ClaimPolicy, signing_key: &SigningKey, key_source: &str) -> Breakdown {
+    let verification = verify_report_with_key(report, claim, policy, &signing_key.modulus, &signing_key.exponent);
+    let passed = |name: &str| verification.checks.iter().any(|check| check.check == name && check.passed);
+
+    let quote_body = Binary::from_base64(&report.report.isv_enclave_quote_body).ok()
+        .and_then(|quote_body| decode_quote_body(&quote_body).ok());
+
+    Breakdown {
+        report_id: report.report.id.clone(),
+        timestamp: report.report.timestamp.clone(),
+        quote_status: report.report.isv_enclave_quote_status.clone(),
+        advisory_ids: report.report.advisory_ids.clone(),
+        signing_key: key_source.to_string(),
+        signature_valid: passed("signature"),
+        payload_matches: passed("payload"),
+        quote_body,
+        platform_info: verification.platform_info.map(PlatformInfo::from),
+        accepted: verification.first_failure().is_none(),
+        checks: verification.checks,
+    }
+}
+
+fn print_human(breakdown: &Breakdown) {
+    println!("Report {} from {}", breakdown.report_id, breakdown.timestamp);
+    println!("  quote status: {}", breakdown.quote_status);
+    println!("  advisories: {}", none_if_empty(&breakdown.advisory_ids.join(", ")));
+
+    match &breakdown.quote_body {
+        Some(quote_body) => {
+            println!("Quote body");
+            println!("  version {}, sign type {}, EPID group {}", quote_body.version, quote_body.sign_type, group_id_hex(quote_body.epid_group_id));
+            println!("  QE SVN {}, PCE SVN {}", quote_body.qe_svn, quote_body.pce_svn);
+            println!("  CPUSVN {}", quote_body.cpu_svn);
+            println!("  MRENCLAVE {}", quote_body.mrenclave);
+            println!("  MRSIGNER {}", quote_body.mrsigner);
+            println!("  ISV product {}, ISV SVN {}", quote_body.isv_prod_id, quote_body.isv_svn);
+            println!("  report data {}", quote_body.report_data);
+        }
+        None => println!("Quote body doesn't decode"),
+    }
+
+    match &breakdown.platform_info {
+        Some(pib) => {
+            println!("Platform info blob");
+            println!("  group id {}", pib.gid_hex);
+            println!("  CPUSVN up to date: {}", pib.cpusvn_up_to_date);
+            println!("  EPID group flags: {}", none_if_empty(&pib.epid_group_flags.join(", ")));
+            println!("  TCB evaluation flags: {}", none_if_empty(&pib.tcb_evaluation_flags.join(", ")));
+            println!("  PSE evaluation flags: {}", none_if_empty(&pib.pse_evaluation_flags.join(", ")));
+        }
+        None => println!("Platform info blob doesn't decode"),
+    }
+
+    println!("Checks, with the signature checked against {}", breakdown.signing_key);
+    for check in &breakdown.checks {
+        match &check.reason {
+            Some(reason) => println!("  FAIL {}: {}", check.check, reason),
+            None => println!("  ok   {}", check.check),
+        }
+    }
+
+    println!("{}", if breakdown.accepted { "The policy accepts the report" } else { "The policy refuses the report" });
+}
+
+fn none_if_empty(list: &str) -> &str {
+    if list.is_empty() { "none" } else { list }
+}
+
+fn run(args: Args) -> Result<bool, String> {
+    let report: IASReport = read_json(&args.report)?;
+    let claim: ClaimStruct = read_json(&args.claim)?;
+    let policy: ClaimPolicy = match &args.policy {
+        Some(path) => read_json(path)?,
+        None => ClaimPolicy::default(),
+    };
+    // Exactly the key the contract checks reports against
+    let signing_key = load_signing_key(&MemoryStorage::new()).map_err(|error| error.to_string())?;
+    let key_source = match &args.cert_chain {
+        Some(path) => {
+            let chain = fs::read_to_string(path).map_err(|error| format!("Can't read {}: {}", path, error))?;
+            let (modulus, exponent) = verify_cert_chain(&chain, (INTEL_ROOT_MODULUS, INTEL_ROOT_EXPONENT))
+                .map_err(|error| format!("{}: {}", path, error))?;
+            if (modulus.as_slice(), exponent.as_slice()) != (signing_key.modulus.as_slice(), signing_key.exponent.as_slice()) {
+                return Err(format!("{}: The chain vouches for a different key than the one the contract checks reports against", path));
+            }
+            "Intel's report signing key, as the cert chain vouches for"
+        }
+        None => "Intel's report signing key",
+    };
+
+    let breakdown = breakdown(&report, &claim, &policy, &signing_key, key_source);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&breakdown).map_err(|error| error.to_string())?);
+    } else {
+        print_human(&breakdown);
+    }
+
+    Ok(breakdown.accepted)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epid_verifier_lotto::intelstructs::{INTEL_ROOT_EXPONENT, INTEL_ROOT_MODULUS};
+    use super::{parse_args, verify_cert_chain};
+
+    // A throwaway 1024 bit root CA and a signer it issued, in the order IAS sends its chain in
+    const SIGNER: &str = "-----BEGIN CERTIFICATE-----
+MIICDTCCAXagAwIBAgIUdFMNBvyNyujp4R2vy43GYSAPRhkwDQYJKoZIhvcNAQEL
+BQAwIDEeMBwGA1UEAwwVZXBpZC12ZXJpZnkgdGVzdCByb290MB4XDTI2MTAxODE5
+MDI1MloXDTM2MTAxNTE5MDI1MlowIjEgMB4GA1UEAwwXZXBpZC12ZXJpZnkgdGVz
+dCBzaWduZXIwgZ8wDQYJKoZIhvcNAQEBBQADgY0AMIGJAoGBALyEyYi2p35oydbM
+JEoOf0qyvi79U87IwnvlCFF1gRi9MpjyAr5FOtVN43ZbMWzA4R0B2zIDPhy940w7
+57oTeqpdZW2g8ZQnw3yKBA4iDpTWQ5Y6ocMKdF7T3gGZNtSOwzc+OTu+Qjc6vDcD
+CI/4pUPWjKK15oaxYdZ4A6ETSy7JAgMBAAGjQjBAMB0GA1UdDgQWBBSW75Ysoqy3
+tjbwo+b1/kqwqPfFczAfBgNVHSMEGDAWgBRlHGCGpN+DjUx8V4G1d0d0WubOKDAN
+BgkqhkiG9w0BAQsFAAOBgQApxk2F73YzsPpdif3BhcYEnArIlyL9eHAZVyerf2iO
+olfV09LMGf1oZe+XAMy4qNE2Mm30wQbguaYJkYiDoX3vI1v21sSEGICf00l/rjxV
+kliSAbWno/7CT5RZgSxf1HJhC5l3furmZYfMzk/nfIgYeJVHpmHhh5LN0EIiumDU
+4g==
+-----END CERTIFICATE-----
+";
+
+    const ROOT: &str = "-----BEGIN CERTIFICATE-----
+MIICHDCCAYWgAwIBAgIUP498InE1ewICnsyUqgMBhauEqTQwDQYJKoZIhvcNAQEL
+BQAwIDEeMBwGA1UEAwwVZXBpZC12ZXJpZnkgdGVzdCByb290MB4XDTI2MTAxODE5
+MDI1MloXDTM2MTAxNTE5MDI1MlowIDEeMBwGA1UEAwwVZXBpZC12ZXJpZnkgdGVz
+dCByb290MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDKZ3Py8d7NirMlAV52
+P1Ea8RCFiqS2whlTPnaWdTFIOXA37rFqVDgdpu6hz9krUqvto/xWSfZo0FlrU/dY
+OxFxr9WZug0IX8R5+vOBT8TfqXRnuTCn/8igq/r6yMO+tu/OJbD1P2pL/m7eF4V6
+bKpuLaeX6e9ij0hcJ4dBR2aG/wIDAQABo1MwUTAdBgNVHQ4EFgQUZRxghqTfg41M
+fFeBtXdHdFrmzigwHwYDVR0jBBgwFoAUZRxghqTfg41MfFeBtXdHdFrmzigwDwYD
+VR0TAQH/BAUwAwEB/zANBgkqhkiG9w0BAQsFAAOBgQBqB1qD6DPoMhDkmzcS7moG
+k6OWekGIOzUmRWOA+l9ygQH+NxP0w8mg+rABlapOM5chHMPZ9/+5wHbqEqbBiILV
+GOEnCeacUhRc7NSqGNHjxFOcDCwLXX5ea8fcJubbPpf9e/il41vYc6i4npydqOJJ
+Td5myXQIpLSmpE5FiEKx1Q==
+-----END CERTIFICATE-----
+";
+
+    const SIGNER_MODULUS: &str = "bc84c988b6a77e68c9d6cc244a0e7f4ab2be2efd53cec8c27be50851758118bd3298f202be453ad54de3765b316cc0e11d01db32033e1cbde34c3be7ba137aaa5d656da0f19427c37c8a040e220e94d643963aa1c30a745ed3de019936d48ec3373e393bbe42373abc3703088ff8a543d68ca2b5e686b161d67803a1134b2ec9";
+
+    const ROOT_MODULUS: &str = "ca6773f2f1decd8ab325015e763f511af110858aa4b6c219533e7696753148397037eeb16a54381da6eea1cfd92b52abeda3fc5649f668d0596b53f7583b1171afd599ba0d085fc479faf3814fc4dfa97467b930a7ffc8a0abfafac8c3beb6efce25b0f53f6a4bfe6ede17857a6caa6e2da797e9ef628f485c278741476686ff";
+
+    #[test]
+    fn test_verify_cert_chain() {
+        let root_modulus = hex::decode(ROOT_MODULUS).unwrap();
+        let root = (root_modulus.as_slice(), [0x01, 0x00, 0x01].as_slice());
+        let chain = format!("{}{}", SIGNER, ROOT);
+
+        let (modulus, exponent) = verify_cert_chain(&chain, root).unwrap();
+        assert_eq!(hex::encode(modulus), SIGNER_MODULUS);
+        assert_eq!(exponent, vec![0x01, 0x00, 0x01]);
+
+        // The way IAS sends the chain along
+        let encoded = chain.replace('-', "%2D").replace('\n', "%0A").replace('+', "%2B").replace('/', "%2F");
+        assert_eq!(verify_cert_chain(&encoded, root).unwrap().0, hex::decode(SIGNER_MODULUS).unwrap());
+
+        // The root doesn't have to come along
+        assert_eq!(verify_cert_chain(SIGNER, root).unwrap().0, hex::decode(SIGNER_MODULUS).unwrap());
+
+        // A chain that doesn't lead up to the root, is out of order or was tampered with is refused
+        assert!(verify_cert_chain(&chain, (INTEL_ROOT_MODULUS, INTEL_ROOT_EXPONENT)).is_err());
+        assert!(verify_cert_chain(&format!("{}{}", ROOT, SIGNER), root).is_err());
+        assert!(verify_cert_chain(&chain.replacen("JEoOf0qy", "JEoOf0qz", 1), root).is_err());
+
+        assert!(verify_cert_chain("not a certificate", root).is_err());
+        assert!(verify_cert_chain("-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----", root).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+
+        let parsed = args(&["report.json", "--json", "claim.json", "--policy", "policy.json"]).unwrap();
+        assert_eq!((parsed.report.as_str(), parsed.claim.as_str()), ("report.json", "claim.json"));
+        assert_eq!(parsed.policy.as_deref(), Some("policy.json"));
+        assert!(parsed.json && parsed.cert_chain.is_none());
+
+        assert!(args(&["report.json"]).is_err());
+        assert!(args(&["report.json", "claim.json", "--cert-chain"]).is_err());
+        assert!(args(&["report.json", "claim.json", "--verbose"]).is_err());
+    }
+}
@@ -1,29 +1,35 @@
-use std::mem;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Addr, BankMsg, Binary, CanonicalAddr, Coin, ConversionOverflowError, CosmosMsg, Deps, DepsMut, ensure, ensure_eq, ensure_ne, Env, has_coins, MessageInfo, Order, Response, StdError, StdResult, to_binary, to_json_binary, Uint128, Uint256};
-use cw_storage_plus::Bounder;
-use sha2::{Digest, Sha256};
-use crate::ContractError::Std;
-// use cw2::set_contract_version;
+use cosmwasm_std::{Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Empty, ensure, ensure_eq, Env, Event, from_json, HexBinary, MessageInfo, Order, Response, StdError, StdResult, Storage, to_json_binary, Uint128, Uint256, WasmMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+use cw2::{CONTRACT, set_contract_version};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::intelstructs::{ClaimStruct, IASReport, INTEL_ROOT_EXPONENT, INTEL_ROOT_MODULUS, recover_signature_digest};
-use crate::msg::{ExecuteMsg, FetchDonorsResponse, FetchRewardedResponse, FetchSeenGroupIds, InstantiateMsg, QueryMsg};
-use crate::platforminfoblob::{convert_claim_struct_to_payload, convert_platform_info_hex, get_payload_from_quote_body};
-use crate::state::{DonationInfo, DONATIONS, REWARD_POT, REWARDED, RewardInfo, RewardPot, SEEN_GROUP_IDS};
+use crate::events::{ClaimEvent, DonateEvent};
+use crate::intelstructs::{ClaimStruct, IASReport};
+use crate::msg::{AttestedEnclave, CampaignResponse, ClaimantResponse, ClaimsResponse, CreateCampaignMsg, DonationEntryInfo, DonorResponse, ExecuteMsg, FetchCampaignsResponse, FetchDonationsResponse, FetchDonorsResponse, FetchRewardedResponse, FetchSeenGroupIds, group_id_hex, GroupIdResponse, InstantiateMsg, IsAttestedResponse, MigrateMsg, PauseResponse, PotStatsResponse, QueryMsg, ReceiveMsg, RolesResponse, SeenGroupId, VerifyReportResponse};
+use crate::migrations::run_migrations;
+use crate::verify::{ReportCheck, verify_report_with_key};
+#[cfg(feature = "private-queries")]
+use crate::msg::QueryWithPermit;
+#[cfg(feature = "private-queries")]
+use crate::permits::{Permit, REVOKED_PERMITS};
+#[cfg(feature = "private-queries")]
+use crate::viewing_keys::{check_viewing_key, pad_query_response, set_viewing_key as set_viewing_key_hash};
+use crate::platforminfoblob::get_mrenclave_from_quote_body;
+use crate::state::{Attestation, AttestationRequirements, ATTESTATION_TTL, ATTESTATIONS, find_attestation, load_attestation_ttl, Campaign, CAMPAIGNS, CLAIMS, ClaimRecord, save_claim_record, ClaimPolicy, DONATION_ENTRIES, DonationInfo, DONATIONS, has_role, NEXT_CAMPAIGN_ID, Ownership, OWNERSHIP, PAUSE, PauseState, Payout, REWARD_POTS, REWARDED, RewardCurve, RewardInfo, RewardPot, Role, role_holders, ROLES, SEEN_GROUP_IDS, load_signing_key, TokenBalance, top_donors};
 
-/*
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:epid-verifier-lotto";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-*/
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     ensure! {
@@ -31,15 +37,94 @@ pub fn instantiate(
         StdError::generic_err("Total reward shares must be greater than 0")
     };
 
-    let state = RewardPot {
-        total_reward_units: msg.total_reward_shares,
-        GEIDs: Default::default(),
-        pot_of_rewards: Default::default(),
+    // The owner and the admin are whoever instantiated us unless someone else was named
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender.clone()
+    };
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender
+    };
+    OWNERSHIP.save(deps.storage, &Ownership { owner, pending_owner: None })?;
+
+    // Reports are checked against Intel's key, only tests get to trust another
+    #[cfg(test)]
+    if let Some(signing_key) = msg.signing_key {
+        signing_key.validate()?;
+        crate::state::SIGNING_KEY.save(deps.storage, &signing_key)?;
+    }
+
+    if let Some(attestation_ttl) = msg.attestation_ttl {
+        ensure!(attestation_ttl > 0, StdError::generic_err("Attestations have to count for some time"));
+        ATTESTATION_TTL.save(deps.storage, &attestation_ttl)?;
+    }
+
+    // The default campaign takes claims from any enclave at any time
+    let default_campaign = Campaign {
+        name: "default".to_string(),
+        admin,
+        policy: ClaimPolicy::default(),
+        start_time: None,
+        end_time: None,
+        donation_cutoff: None,
+        treasury: None,
+    };
+
+    save_new_campaign(deps.storage, &default_campaign, msg.total_reward_shares, RewardCurve::ShareOfPot {})?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // Contracts from before version tracking have no version stored, every migration step applies to them
+    let from = match CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            ensure_eq!(stored.contract, CONTRACT_NAME, StdError::generic_err(format!("Can't migrate from {}, it is a different contract", stored.contract)));
+            parse_version(&stored.version)?
+        }
+        None => Version::new(0, 0, 0)
     };
+    let to = parse_version(CONTRACT_VERSION)?;
+    ensure!(from <= to, StdError::generic_err(format!("Can't migrate from version {} down to {}", from, to)));
+
+    let steps = run_migrations(deps.branch(), &env, &msg, &from)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default()
+        .add_attribute("from_version", from.to_string())
+        .add_attribute("to_version", to.to_string())
+        .add_attribute("migration_steps", steps.join(",")))
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version).map_err(|error| StdError::generic_err(format!("Invalid contract version {}: {}", version, error)))
+}
 
-    REWARD_POT.save(deps.storage, &state).unwrap();
+/// Saves a campaign, along with its empty reward pot, under the next campaign ID and returns that ID.
+fn save_new_campaign(storage: &mut dyn Storage, campaign: &Campaign, total_reward_units: Uint256, reward_curve: RewardCurve) -> StdResult<u64> {
+    let campaign_id = NEXT_CAMPAIGN_ID.may_load(storage)?.unwrap_or_default();
+    NEXT_CAMPAIGN_ID.save(storage, &(campaign_id + 1))?;
 
-   Ok(Response::new())
+    CAMPAIGNS.save(storage, campaign_id, campaign)?;
+    REWARD_POTS.save(storage, campaign_id, &RewardPot::new(campaign_id, total_reward_units, reward_curve))?;
+
+    Ok(campaign_id)
+}
+
+/// The denom of the native coins donated to and paid out of the pots
+pub const NATIVE_DENOM: &str = "ujuno";
+
+/// Loads a campaign along with its reward pot, failing if there is no campaign with that ID.
+fn load_campaign(storage: &dyn Storage, campaign_id: u64) -> StdResult<(Campaign, RewardPot)> {
+    let campaign = CAMPAIGNS.may_load(storage, campaign_id)?
+        .ok_or_else(|| StdError::not_found(format!("Campaign {}", campaign_id)))?;
+    let pot_data = REWARD_POTS.load(storage, campaign_id)?;
+
+    Ok((campaign, pot_data))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -48,238 +133,1217 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ClaimGEID { report, to_claim } => claim_geid(deps, env, info, report, to_claim),
-        ExecuteMsg::DonateToPot { } => donate_to_pot(deps, env, info)
+        ExecuteMsg::ClaimGEID { campaign_id, report, to_claim } => claim_geid(deps, env, info, campaign_id, report, to_claim),
+        ExecuteMsg::Attest { report, to_claim } => attest(deps, env, info, report, to_claim),
+        ExecuteMsg::DonateToPot { campaign_id, expires_at } => donate_to_pot(deps, env, info, campaign_id, expires_at),
+        ExecuteMsg::Receive(receive_msg) => receive_cw20(deps, env, info, receive_msg),
+        ExecuteMsg::WithdrawDonation { campaign_id } => withdraw_donation(deps, env, info, campaign_id),
+        ExecuteMsg::CancelCampaign { campaign_id } => cancel_campaign(deps, info, campaign_id),
+        ExecuteMsg::CreateCampaign(create_msg) => create_campaign(deps, info, create_msg),
+        ExecuteMsg::Sweep { campaign_id, limit } => sweep(deps, env, info, campaign_id, limit),
+        ExecuteMsg::TransferOwnership { new_owner } => transfer_ownership(deps, info, new_owner),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+        ExecuteMsg::GrantRole { role, address } => grant_role(deps, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => revoke_role(deps, info, role, address),
+        ExecuteMsg::UpdatePolicy { campaign_id, policy } => update_policy(deps, info, campaign_id, policy),
+        ExecuteMsg::RevokeMrenclave { campaign_id, mrenclave } => revoke_mrenclave(deps, info, campaign_id, mrenclave),
+        ExecuteMsg::Pause { claims, donations, reason, until } => pause(deps, env, info, claims, donations, reason, until),
+        ExecuteMsg::Unpause {} => unpause(deps, info),
+        ExecuteMsg::RevokeAttestations { address } => revoke_attestations(deps, info, address),
+        #[cfg(feature = "private-queries")]
+        ExecuteMsg::SetViewingKey { key, .. } => set_viewing_key(deps, info, key),
+        #[cfg(feature = "private-queries")]
+        ExecuteMsg::RevokePermit { permit_name, .. } => revoke_permit(deps, info, permit_name)
     }
 }
 
-fn claim_geid(deps: DepsMut, env: Env, info: MessageInfo, report: IASReport, claim_struct: ClaimStruct) -> StdResult<Response> {
-    // Load the reward pot from the state
-    let mut pot_data = REWARD_POT.load(deps.storage).unwrap();
-    // Take the message sender and convert them from human address to canonical
-    let canonical_claimant = deps.api.addr_canonicalize(info.sender.as_str()).unwrap();
+/// The key is the claimant's to pick, its hash is all we keep
+#[cfg(feature = "private-queries")]
+fn set_viewing_key(deps: DepsMut, info: MessageInfo, key: String) -> Result<Response, ContractError> {
+    set_viewing_key_hash(deps.storage, &info.sender, &key)?;
+
+    // Neither the key nor who set it go in the response
+    Ok(Response::default())
+}
+
+#[cfg(feature = "private-queries")]
+fn revoke_permit(deps: DepsMut, info: MessageInfo, permit_name: String) -> Result<Response, ContractError> {
+    REVOKED_PERMITS.save(deps.storage, (&info.sender, &permit_name), &Empty {})?;
+
+    Ok(Response::default())
+}
+
+/// Who may send a privileged message. The owner holds every role, but a campaign's pot is only its admin's to touch.
+enum Permission<'a> {
+    Owner,
+    Role(Role),
+    /// The admin of a campaign, or whoever holds the role
+    CampaignAdminOr(&'a Campaign, Role),
+    /// The admin of a campaign and nobody else
+    CampaignAdmin(&'a Campaign)
+}
+
+/// Every privileged message goes through here before it touches anything
+fn ensure_permitted(storage: &dyn Storage, sender: &Addr, permission: Permission) -> Result<(), ContractError> {
+    let permitted = match permission {
+        Permission::Owner => OWNERSHIP.load(storage)?.owner == *sender,
+        Permission::Role(role) => has_role(storage, sender, role)?,
+        Permission::CampaignAdminOr(campaign, role) => campaign.admin == *sender || has_role(storage, sender, role)?,
+        Permission::CampaignAdmin(campaign) => campaign.admin == *sender,
+    };
+    ensure!(permitted, ContractError::Unauthorized {});
+
+    Ok(())
+}
+
+/// A policy's MRENCLAVEs have to be MRENCLAVEs and its bonus tiers have to be tiers
+fn validate_policy(policy: &ClaimPolicy) -> StdResult<()> {
+    let mrenclaves = policy.accepted_mrenclaves.iter().chain(&policy.revoked_mrenclaves);
+    ensure!(mrenclaves.into_iter().all(|mrenclave| mrenclave.len() == 32), StdError::generic_err("An MRENCLAVE is 32 bytes long"));
+    ensure!(policy.bonus_tiers.iter().all(|tier| !tier.name.is_empty() && !tier.reward_shares.is_zero()), StdError::generic_err("A bonus tier needs a name and reward shares"));
+
+    Ok(())
+}
+
+fn transfer_ownership(deps: DepsMut, info: MessageInfo, new_owner: String) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Owner)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+
+    // Handing the contract over to someone else replaces whoever it was being handed to before
+    let mut ownership = OWNERSHIP.load(deps.storage)?;
+    ownership.pending_owner = Some(new_owner.clone());
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::default().add_attribute("pending_owner", new_owner))
+}
+
+fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut ownership = OWNERSHIP.load(deps.storage)?;
+    ensure!(ownership.pending_owner.as_ref() == Some(&info.sender), ContractError::Unauthorized {});
+
+    ownership.owner = info.sender;
+    ownership.pending_owner = None;
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::default().add_attribute("owner", ownership.owner))
+}
+
+fn grant_role(deps: DepsMut, info: MessageInfo, role: Role, address: String) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Owner)?;
+    let holder = deps.api.addr_validate(&address)?;
+
+    ROLES.save(deps.storage, (role.as_str(), &holder), &Empty {})?;
+
+    Ok(Response::default()
+        .add_attribute("granted_role", role.as_str())
+        .add_attribute("holder", holder))
+}
+
+fn revoke_role(deps: DepsMut, info: MessageInfo, role: Role, address: String) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Owner)?;
+    let holder = deps.api.addr_validate(&address)?;
+    ensure!(ROLES.has(deps.storage, (role.as_str(), &holder)), StdError::generic_err(format!("{} isn't a {}", holder, role.as_str())));
+
+    ROLES.remove(deps.storage, (role.as_str(), &holder));
+
+    Ok(Response::default()
+        .add_attribute("revoked_role", role.as_str())
+        .add_attribute("holder", holder))
+}
+
+fn pause(deps: DepsMut, env: Env, info: MessageInfo, claims: bool, donations: bool, reason: Option<String>, until: Option<u64>) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Role(Role::Pauser))?;
+    ensure!(claims || donations, StdError::generic_err("Pause claims, donations or both"));
+    if let Some(until) = until {
+        ensure!(until > env.block.time.seconds(), StdError::generic_err("The pause has to lift in the future"));
+    }
+
+    let pause = PauseState { claims, donations, reason, paused_by: info.sender, paused_at: env.block.time.seconds(), until };
+    PAUSE.save(deps.storage, &pause)?;
+
+    let mut event = Event::new("pause")
+        .add_attribute("claims", claims.to_string())
+        .add_attribute("donations", donations.to_string())
+        .add_attribute("paused_by", pause.paused_by);
+    if let Some(reason) = pause.reason {
+        event = event.add_attribute("reason", reason);
+    }
+    if let Some(until) = until {
+        event = event.add_attribute("until", until.to_string());
+    }
+
+    Ok(Response::default().add_event(event))
+}
+
+fn unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Role(Role::Pauser))?;
+    ensure!(PAUSE.exists(deps.storage), StdError::generic_err("Nothing is paused"));
+
+    PAUSE.remove(deps.storage);
+
+    Ok(Response::default().add_event(Event::new("unpause").add_attribute("unpaused_by", info.sender)))
+}
+
+/// Claims can be stopped in an emergency, e.g. when a verification bug is found
+fn ensure_claims_not_paused(storage: &dyn Storage, env: &Env) -> StdResult<()> {
+    if let Some(pause) = PAUSE.may_load(storage)? {
+        ensure!(!pause.claims_paused(env.block.time.seconds()), StdError::generic_err(paused_message("Claims", &pause)));
+    }
 
-    // Grab the hex encoded platform info blob
-    let pib = report.report.platform_info_blob.clone();
-    // Convert the platform info blob from hex to struct, hence the "fr" aka for real
-    let pib_fr = convert_platform_info_hex(pib.as_str()).unwrap();
-    // Because we're storing the group id we want to make this 4 bytes representing the u32
-    let group_id: [u8; 4] = pib_fr.gid.to_be_bytes();
+    Ok(())
+}
+
+/// Donations can be stopped in an emergency as well
+fn ensure_donations_not_paused(storage: &dyn Storage, env: &Env) -> StdResult<()> {
+    if let Some(pause) = PAUSE.may_load(storage)? {
+        ensure!(!pause.donations_paused(env.block.time.seconds()), StdError::generic_err(paused_message("Donations", &pause)));
+    }
+
+    Ok(())
+}
+
+fn paused_message(what: &str, pause: &PauseState) -> String {
+    match &pause.reason {
+        Some(reason) => format!("{} are paused: {}", what, reason),
+        None => format!("{} are paused", what)
+    }
+}
+
+fn update_policy(deps: DepsMut, info: MessageInfo, campaign_id: u64, policy: ClaimPolicy) -> Result<Response, ContractError> {
+    let (mut campaign, _) = load_campaign(deps.storage, campaign_id)?;
+    ensure_permitted(deps.storage, &info.sender, Permission::CampaignAdminOr(&campaign, Role::PolicyAdmin))?;
+    validate_policy(&policy)?;
+
+    campaign.policy = policy;
+    CAMPAIGNS.save(deps.storage, campaign_id, &campaign)?;
+
+    Ok(Response::default().add_attribute("updated_policy", campaign_id.to_string()))
+}
+
+fn revoke_mrenclave(deps: DepsMut, info: MessageInfo, campaign_id: u64, mrenclave: HexBinary) -> Result<Response, ContractError> {
+    let (mut campaign, _) = load_campaign(deps.storage, campaign_id)?;
+    ensure_permitted(deps.storage, &info.sender, Permission::CampaignAdminOr(&campaign, Role::Revoker))?;
+    ensure!(mrenclave.len() == 32, StdError::generic_err("An MRENCLAVE is 32 bytes long"));
+
+    if !campaign.policy.revoked_mrenclaves.contains(&mrenclave) {
+        campaign.policy.revoked_mrenclaves.push(mrenclave.clone());
+        CAMPAIGNS.save(deps.storage, campaign_id, &campaign)?;
+    }
+
+    Ok(Response::default().add_attribute("revoked_mrenclave", mrenclave.to_hex()))
+}
+
+fn revoke_attestations(deps: DepsMut, info: MessageInfo, address: String) -> Result<Response, ContractError> {
+    ensure_permitted(deps.storage, &info.sender, Permission::Role(Role::Revoker))?;
+    let address = deps.api.addr_validate(&address)?;
 
-    // Load the ISV enclave quote body, encoded as base64, into a `Binary` struct
-    let quote_body = Binary::from_base64(report.report.isv_enclave_quote_body.as_str()).unwrap();
+    let mrenclaves = ATTESTATIONS.prefix(&address)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    for mrenclave in &mrenclaves {
+        ATTESTATIONS.remove(deps.storage, (&address, mrenclave));
+    }
+
+    Ok(Response::default()
+        .add_attribute("revoked_attestations", address)
+        .add_attribute("count", mrenclaves.len().to_string()))
+}
+
+fn create_campaign(deps: DepsMut, info: MessageInfo, msg: CreateCampaignMsg) -> Result<Response, ContractError> {
+    ensure!(msg.total_reward_units > Uint256::zero(), StdError::generic_err("Total reward shares must be greater than 0"));
+    if let (Some(start_time), Some(end_time)) = (msg.start_time, msg.end_time) {
+        ensure!(start_time < end_time, StdError::generic_err("The campaign has to start before it ends"));
+    }
+
+    // The admin is whoever created the campaign unless someone else was named
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender
+    };
+    let treasury = msg.treasury.map(|treasury| deps.api.addr_validate(&treasury)).transpose()?;
+
+    let policy = ClaimPolicy {
+        accepted_quote_statuses: msg.accepted_quote_statuses,
+        accepted_mrenclaves: msg.accepted_mrenclaves,
+        bonus_tiers: msg.bonus_tiers,
+        revoked_mrenclaves: vec![],
+    };
+    validate_policy(&policy)?;
+
+    let campaign = Campaign {
+        name: msg.name,
+        admin,
+        policy,
+        start_time: msg.start_time,
+        end_time: msg.end_time,
+        donation_cutoff: msg.donation_cutoff,
+        treasury,
+    };
 
-    // Extract the payload from the quote body
-    let payload = get_payload_from_quote_body(quote_body.as_slice());
+    let campaign_id = save_new_campaign(deps.storage, &campaign, msg.total_reward_units, msg.reward_curve)?;
 
-    // Convert the claim struct to the stringified claim struct
-    let hashed_claim_struct = convert_claim_struct_to_payload(claim_struct);
+    Ok(Response::default().add_attribute("campaign_id", campaign_id.to_string()))
+}
 
-    // Ensure that the claim struct generated by an enclave, stored in the payload field, is equal to the one that was passed in..
-    ensure_eq!(payload, hashed_claim_struct, StdError::generic_err("Payload and ClaimStruct do not have matching content."));
+/// Whether a campaign is taking claims right now
+fn ensure_taking_claims(env: &Env, campaign: &Campaign, pot_data: &RewardPot) -> StdResult<()> {
+    // A cancelled campaign doesn't pay out anymore, what is left belongs to the donors
+    ensure!(!pot_data.cancelled, StdError::generic_err("The campaign has been cancelled"));
+    // Claims are only taken while the campaign is running
+    ensure!(campaign.is_open(env.block.time.seconds()), StdError::generic_err("The campaign isn't taking claims right now"));
 
-    // Convert the ias report as a json string, removing all the backslashes to escape stuff
-    let ias_report_asjson = serde_json::to_string(&report.report).unwrap().replace("\\", "");
-    // Convert the ias report without the back slashes to bytes
-    let ias_report_asbytes = ias_report_asjson.as_bytes();
+    Ok(())
+}
 
-    // Convert the base64 ias report signature into a Binary object from base64
-    let ias_report_sig = Binary::from_base64(report.report_sig.as_str()).unwrap();
-    // Convert the base64 object into a byte slice
-    let ias_report_sig_bytes = ias_report_sig.as_slice();
+/// Every group id can only claim from a campaign once
+fn ensure_group_id_unseen(storage: &dyn Storage, campaign_id: u64, group_id: u32) -> Result<(), ContractError> {
+    match SEEN_GROUP_IDS.may_load(storage, (campaign_id, group_id))? {
+        Some(claimant) => Err(ContractError::GroupAlreadyClaimed { gid: group_id, claimant }),
+        None => Ok(())
+    }
+}
 
-    // Recover the RSA signature's digest
-    let recovered_digest = recover_signature_digest(ias_report_sig_bytes, INTEL_ROOT_EXPONENT, INTEL_ROOT_MODULUS);
-    // Convert the recovered digest into a byte slice
-    let recovered_digest = recovered_digest.as_slice();
+/// Anyone can submit a report once it has been claimed with, only the account its claim names gets to use it
+fn ensure_claim_is_for(claim: &ClaimStruct, claimant: &CanonicalAddr) -> Result<(), ContractError> {
+    ensure!(claim.is_for(claimant.as_slice()), ContractError::ClaimantMismatch {});
 
-    // We are going to calculate our own digest of the ias report
-    let mut hasher = Sha256::default();
+    Ok(())
+}
 
-    // Update the hasher contents with that of the ias report json
-    hasher.update(ias_report_asbytes);
+fn claim_geid(deps: DepsMut, env: Env, info: MessageInfo, campaign_id: u64, report: IASReport, claim_struct: ClaimStruct) -> Result<Response, ContractError> {
+    // Nothing is paid out while claims are paused
+    ensure_claims_not_paused(deps.storage, &env)?;
+    // Load the campaign and its reward pot from the state
+    let (campaign, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    ensure_taking_claims(&env, &campaign, &pot_data)?;
+    // Take the message sender and convert them from human address to canonical
+    let canonical_claimant = deps.api.addr_canonicalize(info.sender.as_str())?;
 
-    // finalize the sha256 hasher so that it produces a byte slice of the digest, should be 32 bytes
-    let sha256d_claim = &hasher.finalize()[..];
+    // Check the report against the campaign's policy, that it carries the claim and that Intel signed it
+    // and hand us the platform info blob that was parsed along the way
+    let signing_key = load_signing_key(deps.storage)?;
+    let pib_fr = verify_report_with_key(&report, &claim_struct, &campaign.policy, &signing_key.modulus, &signing_key.exponent).into_platform_info()?;
+    let group_id = pib_fr.group_id();
+    // the report pays out to whoever its claim names, replaying somebody else's report gets nothing
+    ensure_claim_is_for(&claim_struct, &canonical_claimant)?;
 
-    // ensure that the recovered digest from the signature matches the digest of the report
-    ensure_eq!(sha256d_claim, recovered_digest, StdError::generic_err("The recovered digest doesn't match the submitted Report's digest"));
     // ensure that we haven't seen the group id we want to claim before
-    ensure!(SEEN_GROUP_IDS.has(deps.storage, group_id.to_vec()) != true, StdError::generic_err(format!("Group ID : {:?} has already been seen before!", group_id)));
+    ensure_group_id_unseen(deps.storage, campaign_id, group_id)?;
     // add the group id to `SEEN_GROUP_IDS` so that the previous check fails on an already claimed Group ID
-    SEEN_GROUP_IDS.save(deps.storage, group_id.to_vec(), &canonical_claimant.to_vec()).unwrap();
+    SEEN_GROUP_IDS.save(deps.storage, (campaign_id, group_id), &info.sender)?;
+
+    // the report is an attestation that the claimant runs the enclave on SGX hardware, other contracts can check it
+    // with `IsAttested`
+    save_attestation(deps.storage, &env, &info.sender, &report, group_id)?;
+
+    // platforms that are kept patched may be in for a bonus
+    let bonus_tier = campaign.policy.bonus_tier(&report.report.isv_enclave_quote_status, pib_fr.cpusvn_is_up_to_date());
 
     // calculate the rewards that the claimant will get
-    let claimed = pot_data.claim_rewards(deps.storage, canonical_claimant, env.block.time.seconds());
+    let claimed = pot_data.claim_rewards(deps.storage, canonical_claimant.clone(), env.block.time.seconds(), bonus_tier)?;
 
-    // lets fit the reward into a Uint128, if the reward is too large that is going to be a rip
-    let claimed_to_u128 = match Uint128::try_from(claimed) {
+    // build the messages that hand the claimant their share of every coin and token in the pot
+    let payout_messages = payout_messages(&info.sender, &claimed)?;
+
+    // remove from the pot of rewards the amount that was claimed
+    pot_data.deduct_payout(&claimed);
+
+    // save the changes done to the reward pot
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
+
+    // keep a record of the claim so its history can be looked up later
+    save_claim_record(deps.storage, &ClaimRecord {
+        campaign_id,
+        group_id,
+        claimant: info.sender.clone(),
+        payout: claimed.clone(),
+        denom: NATIVE_DENOM.to_string(),
+        quote_status: report.report.isv_enclave_quote_status.clone(),
+        report_id: report.report.id.clone(),
+        bonus_tier: bonus_tier.map(|tier| tier.name.clone()),
+        block_height: env.block.height,
+        claim_time: env.block.time.seconds(),
+    })?;
+
+    // the reward shares the claim was paid out on, they were set by the claimant's first claim
+    let reward_shares = REWARDED.load(deps.storage, (campaign_id, canonical_claimant.to_vec()))?.reward_shares;
+
+    let claim_event = ClaimEvent {
+        campaign_id,
+        claimant: &info.sender,
+        group_id,
+        epid_pseudonym: report.report.epid_pseudonym.as_slice(),
+        quote_status: &report.report.isv_enclave_quote_status,
+        report_id: &report.report.id,
+        payout: &claimed,
+        reward_shares,
+        bonus_tier: bonus_tier.map(|tier| tier.name.as_str()),
+        pot_balance: &pot_data.balance(),
+    };
+
+    // send a response from the contract that transfers the amount of funds the claimant was meant to get
+    Ok(Response::default()
+        .add_messages(payout_messages)
+        .add_event(claim_event.into_event()))
+}
+
+/// Attests the sender without claiming, so platforms can renew their attestation or attest from a group that
+/// already claimed, whatever state the campaigns are in.
+fn attest(deps: DepsMut, env: Env, info: MessageInfo, report: IASReport, claim_struct: ClaimStruct) -> Result<Response, ContractError> {
+    let canonical_sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // No campaign's policy applies, any report Intel signed that carries the claim will do. Whoever checks
+    // `IsAttested` picks the enclaves and quote statuses they trust.
+    let signing_key = load_signing_key(deps.storage)?;
+    let pib_fr = verify_report_with_key(&report, &claim_struct, &ClaimPolicy::default(), &signing_key.modulus, &signing_key.exponent).into_platform_info()?;
+    // the report attests whoever its claim names, replaying somebody else's report attests nobody
+    ensure_claim_is_for(&claim_struct, &canonical_sender)?;
+
+    let attestation = save_attestation(deps.storage, &env, &info.sender, &report, pib_fr.group_id())?;
+
+    let mut response = Response::default().add_attribute("action", "attest");
+    // The private-queries build doesn't tell which enclave an address runs, just like `IsAttested`
+    if cfg!(not(feature = "private-queries")) {
+        response = response
+            .add_attribute("mrenclave", attestation.mrenclave.to_hex())
+            .add_attribute("expires_at", attestation.expires_at.to_string());
+    }
+
+    Ok(response)
+}
+
+/// Saves what a verified report attests: that `address` runs the enclave in it on SGX hardware, from when IAS made
+/// the report until the attestation TTL runs out. It replaces the address' earlier attestation from that enclave.
+fn save_attestation(storage: &mut dyn Storage, env: &Env, address: &Addr, report: &IASReport, group_id: u32) -> Result<Attestation, ContractError> {
+    // The report passed verification, so its quote body holds an MRENCLAVE
+    let mrenclave = get_mrenclave_from_quote_body(&Binary::from_base64(&report.report.isv_enclave_quote_body)?)?;
+    // the attestation is as old as the report, which can't be newer than the block it is submitted in
+    let attested_at = report.report.timestamp_seconds()?.min(env.block.time.seconds());
+    let expires_at = attested_at.saturating_add(load_attestation_ttl(storage)?);
+    let attestation = Attestation {
+        group_id,
+        mrenclave: HexBinary::from(mrenclave.as_slice()),
+        quote_status: report.report.isv_enclave_quote_status.clone(),
+        attested_at,
+        expires_at,
+    };
+    ATTESTATIONS.save(storage, (address, &mrenclave), &attestation)?;
+
+    Ok(attestation)
+}
+
+/// Builds the messages that send a payout to the claimant, a bank send for the native coins and a CW20 transfer
+/// for every token. Empty amounts are skipped since the chain refuses to send nothing.
+fn payout_messages(claimant: &Addr, payout: &Payout) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = Vec::new();
+
+    if !payout.native.is_zero() {
+        // lets fit the reward into a Uint128, if the reward is too large that is going to be a rip
+        let native_to_u128 = reward_to_u128(payout.native)?;
+
+        // build a Coin struct to use to send coins
+        let coin_to_send = Coin::new(native_to_u128.u128(), NATIVE_DENOM);
+
+        messages.push(CosmosMsg::Bank(BankMsg::Send { to_address: claimant.to_string(), amount: vec![coin_to_send] }));
+    }
+
+    for token_reward in &payout.cw20 {
+        if token_reward.amount.is_zero() {
+            continue;
+        }
+
+        let transfer = Cw20ExecuteMsg::Transfer {
+            recipient: claimant.to_string(),
+            amount: reward_to_u128(token_reward.amount)?,
+        };
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token_reward.token.to_string(),
+            msg: to_json_binary(&transfer)?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(messages)
+}
+
+/// Coins and CW20 tokens are sent as `Uint128`s, so our `Uint256` rewards need to fit into one.
+fn reward_to_u128(reward: Uint256) -> StdResult<Uint128> {
+    match Uint128::try_from(reward) {
         Ok(amt) => {
-            amt
+            Ok(amt)
         }
         Err(_) => {
-            return Err(StdError::generic_err("We're trying to convert too large of a number to a Uint128, lets hope this never happens ^-^"))
+            Err(StdError::generic_err("We're trying to convert too large of a number to a Uint128, lets hope this never happens ^-^"))
         }
+    }
+}
+
+/// Donations can be locked until a time in the future, after which the donor can take back what is left of them.
+fn ensure_valid_expiry(env: &Env, expires_at: Option<u64>) -> StdResult<()> {
+    if let Some(expires_at) = expires_at {
+        ensure!(expires_at > env.block.time.seconds(), StdError::generic_err("The donation has to expire in the future"));
+    }
+
+    Ok(())
+}
+
+/// Donations are only taken while the campaign is running and its donations haven't been cut off.
+fn ensure_taking_donations(env: &Env, campaign: &Campaign, pot_data: &RewardPot) -> StdResult<()> {
+    ensure!(!pot_data.cancelled, StdError::generic_err("The campaign has been cancelled"));
+    ensure!(pot_data.sweep.is_none(), StdError::generic_err("The campaign's leftovers have been swept"));
+    ensure!(campaign.is_taking_donations(env.block.time.seconds()), StdError::generic_err("The campaign isn't taking donations anymore"));
+
+    Ok(())
+}
+
+fn donate_to_pot(deps: DepsMut, env: Env, info: MessageInfo, campaign_id: u64, expires_at: Option<u64>) -> Result<Response, ContractError> {
+    ensure_donations_not_paused(deps.storage, &env)?;
+
+    // Figure out the amount of juno that was donated, it has to be a minimum of 0.000001 juno. Other coins may have
+    // been sent along with it, so it isn't necessarily the first one.
+    let amount_donated = info.funds.iter()
+        .find(|coin| coin.denom == NATIVE_DENOM && !coin.amount.is_zero())
+        .map(|coin| Uint256::from(coin.amount))
+        .ok_or(ContractError::NoFunds {})?;
+
+    ensure_valid_expiry(&env, expires_at)?;
+
+    // Load the reward pot
+    let (campaign, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    ensure_taking_donations(&env, &campaign, &pot_data)?;
+
+    // The donor is the message sender
+    let donor = info.sender;
+
+    // Canonicalize the donor address
+    let canonicalized_donor = deps.api.addr_canonicalize(donor.as_str())?;
+
+    // Add the donation to the pot
+    pot_data.add_donation(deps.storage, canonicalized_donor, amount_donated, env.block.time.seconds(), expires_at)?;
+
+    // Save the reward pot with its changes
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
+
+    let donate_event = DonateEvent {
+        campaign_id,
+        donor: &donor,
+        donation: &Payout { native: amount_donated, cw20: vec![] },
+        expires_at,
+        pot_balance: &pot_data.balance(),
     };
 
-    // remove from the pot of rewards the amount that was claimed
-    pot_data.pot_of_rewards -= claimed;
+    // Emit an event from the contract that indicates a donation was done
+    Ok(Response::default()
+        .add_event(donate_event.into_event())
+        .add_attribute("donation", amount_donated.to_string()))
+}
 
-    // build a Coin struct to use to send coins
-    let coin_to_send = Coin::new(claimed_to_u128.u128(), "ujuno");
+fn receive_cw20(deps: DepsMut, env: Env, info: MessageInfo, receive_msg: Cw20ReceiveMsg) -> Result<Response, ContractError> {
+    // The message sender is the CW20 token contract, the one who sent the tokens is inside the receive message
+    let token = info.sender;
 
-    // save the changes done to the reward pot
-    REWARD_POT.save(deps.storage, &pot_data).unwrap();
+    match from_json(&receive_msg.msg)? {
+        ReceiveMsg::DonateToPot { campaign_id, expires_at } => donate_cw20_to_pot(deps, env, token, receive_msg, campaign_id, expires_at)
+    }
+}
 
-    // send a response from the contract that transfers the amount of funds the claimant was meant to get
-    Ok(Response::default().add_message(CosmosMsg::Bank(BankMsg::Send { to_address: info.sender.to_string(), amount: vec![coin_to_send] })))
+fn donate_cw20_to_pot(deps: DepsMut, env: Env, token: Addr, receive_msg: Cw20ReceiveMsg, campaign_id: u64, expires_at: Option<u64>) -> Result<Response, ContractError> {
+    ensure_donations_not_paused(deps.storage, &env)?;
+    ensure!(!receive_msg.amount.is_zero(), ContractError::NoFunds {});
+    ensure_valid_expiry(&env, expires_at)?;
+
+    // Load the reward pot
+    let (campaign, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    ensure_taking_donations(&env, &campaign, &pot_data)?;
+
+    // Figure out the amount of tokens that were donated
+    let amount_donated = Uint256::from(receive_msg.amount);
+
+    // The donor is whoever sent the tokens to us, canonicalize them
+    let donor = deps.api.addr_validate(&receive_msg.sender)?;
+    let canonicalized_donor = deps.api.addr_canonicalize(donor.as_str())?;
+
+    // Add the donation to the pot
+    pot_data.add_cw20_donation(deps.storage, canonicalized_donor, &token, amount_donated, env.block.time.seconds(), expires_at)?;
+
+    // Save the reward pot with its changes
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
+
+    let donate_event = DonateEvent {
+        campaign_id,
+        donor: &donor,
+        donation: &Payout { native: Uint256::zero(), cw20: vec![TokenBalance { token: token.clone(), amount: amount_donated }] },
+        expires_at,
+        pot_balance: &pot_data.balance(),
+    };
+
+    // Emit an event from the contract that indicates a donation was done
+    Ok(Response::default()
+        .add_event(donate_event.into_event())
+        .add_attribute("donation", amount_donated.to_string())
+        .add_attribute("token", token))
 }
 
-fn donate_to_pot(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-    // Check if the transfer has sent a minimum of 0.000001 juno
-    if has_coins(&info.funds, &Coin { denom: "ujuno".to_string(), amount: Uint128::from(1u128) }) {
-        // Load the reward pot
-        let mut pot_data = REWARD_POT.load(deps.storage).unwrap();
+fn withdraw_donation(deps: DepsMut, env: Env, info: MessageInfo, campaign_id: u64) -> Result<Response, ContractError> {
+    let (_, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    // Once the sweep has started the leftovers are accounted for, so nobody can take from them on their own
+    ensure!(pot_data.sweep.is_none(), StdError::generic_err("The campaign's leftovers have been swept"));
 
-        // Figure out the amount of juno that was donated
-        let amount_donated = Uint256::from(info.funds.as_slice().get(0).unwrap().amount);
+    let canonicalized_donor = deps.api.addr_canonicalize(info.sender.as_str())?;
 
-        // The donor is the message sender
-        let donor = info.sender;
+    // Take whatever is left of the donations that expired, or all of them if the campaign was cancelled
+    let withdrawn = pot_data.withdraw_donations(deps.storage, canonicalized_donor, env.block.time.seconds())?;
 
-        // Canonicalize the donor address
-        let canonicalized_donor = deps.api.addr_canonicalize(donor.as_str()).unwrap();
+    let refund_messages = payout_messages(&info.sender, &withdrawn)?;
+    ensure!(!refund_messages.is_empty(), StdError::generic_err("There is nothing to withdraw"));
 
-        // Add the donation to the pot
-        pot_data.add_donation(deps.storage, canonicalized_donor, amount_donated, env.block.time.seconds()).unwrap();
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
 
-        // Save the reward pot with its changes
-        REWARD_POT.save(deps.storage, &pot_data).unwrap();
+    Ok(Response::default()
+        .add_messages(refund_messages)
+        .add_attribute("withdrawal", withdrawn.native.to_string()))
+}
 
-        // Emit an event from the contract that indicates a donation was done
-        Ok(Response::default().add_attribute("donation", amount_donated.to_string()))
-    } else {
-        // Inform the user that their act of wanting to donate is appreciated :)
-        Err(StdError::generic_err("Your generous donation has been noted :) Thank you!"))
+fn cancel_campaign(deps: DepsMut, info: MessageInfo, campaign_id: u64) -> Result<Response, ContractError> {
+    let (campaign, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    ensure_permitted(deps.storage, &info.sender, Permission::CampaignAdmin(&campaign))?;
+
+    pot_data.cancelled = true;
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
+
+    Ok(Response::default().add_attribute("cancelled", "true"))
+}
+
+/// The number of donors paid back by a single sweep when no limit is given
+const DEFAULT_SWEEP_LIMIT: u32 = 30;
+/// The most donors paid back by a single sweep
+const MAX_SWEEP_LIMIT: u32 = 100;
+
+fn sweep(deps: DepsMut, env: Env, info: MessageInfo, campaign_id: u64, limit: Option<u32>) -> Result<Response, ContractError> {
+    let (campaign, mut pot_data) = load_campaign(deps.storage, campaign_id)?;
+    ensure_permitted(deps.storage, &info.sender, Permission::CampaignAdmin(&campaign))?;
+    ensure!(campaign.has_ended(env.block.time.seconds()), StdError::generic_err("The campaign hasn't ended yet"));
+    ensure!(!pot_data.sweep.as_ref().is_some_and(|progress| progress.finished), StdError::generic_err("The campaign's leftovers have been swept"));
+
+    let mut sweep_messages = Vec::new();
+
+    // What is left of a cancelled campaign belongs to its donors, it never goes to the treasury
+    let treasury = campaign.treasury.as_ref().filter(|_| !pot_data.cancelled);
+
+    match treasury {
+        Some(treasury) => {
+            // Everything goes to the treasury in one go
+            let leftovers = pot_data.sweep_to_treasury();
+            sweep_messages.extend(payout_messages(treasury, &leftovers)?);
+        }
+        None => {
+            // The leftovers go back to the donors, a batch at a time
+            let limit = limit.unwrap_or(DEFAULT_SWEEP_LIMIT).clamp(1, MAX_SWEEP_LIMIT) as usize;
+
+            for (donor, payout) in pot_data.sweep_to_donors(deps.storage, limit)? {
+                let donor = deps.api.addr_humanize(&CanonicalAddr::from(donor))?;
+                sweep_messages.extend(payout_messages(&donor, &payout)?);
+            }
+        }
     }
+
+    let finished = pot_data.sweep.as_ref().is_some_and(|progress| progress.finished);
+    REWARD_POTS.save(deps.storage, campaign_id, &pot_data)?;
+
+    Ok(Response::default()
+        .add_messages(sweep_messages)
+        .add_attribute("sweep", campaign_id.to_string())
+        .add_attribute("finished", finished.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    #[cfg(feature = "private-queries")]
+    ensure_not_private(&msg)?;
+
     match msg {
-        QueryMsg::FetchDonors { page, page_size } => fetch_donors(deps, page, page_size),
-        QueryMsg::FetchClaimants { page, page_size } => fetch_claimants(deps, page, page_size),
-        QueryMsg::FetchSeenGuids { page, page_size } => fetch_seen_group_ids(deps, page, page_size),
+        QueryMsg::FetchDonors { campaign_id, start_after, limit } => fetch_donors(deps, campaign_id, start_after, limit),
+        QueryMsg::FetchClaimants { campaign_id, start_after, limit } => fetch_claimants(deps, campaign_id, start_after, limit),
+        QueryMsg::FetchSeenGuids { campaign_id, start_after, limit } => fetch_seen_group_ids(deps, campaign_id, start_after, limit),
+        QueryMsg::FetchDonations { campaign_id, donor } => fetch_donations(deps, env, campaign_id, donor),
+        QueryMsg::Campaign { campaign_id } => fetch_campaign(deps, campaign_id),
+        QueryMsg::Donor { campaign_id, address } => fetch_donor(deps, campaign_id, address),
+        QueryMsg::TopDonors { campaign_id, start_after, limit } => fetch_top_donors(deps, campaign_id, start_after, limit),
+        QueryMsg::Claimant { campaign_id, address } => fetch_claimant(deps, campaign_id, address),
+        QueryMsg::GroupId { campaign_id, gid } => fetch_group_id(deps, campaign_id, gid),
+        QueryMsg::ClaimsByAddress { address, start_after, limit } => fetch_claims_by_address(deps, address, start_after, limit),
+        QueryMsg::ClaimsInRange { start_time, end_time, start_after, limit } => fetch_claims_in_range(deps, start_time, end_time, start_after, limit),
+        QueryMsg::PotStats { campaign_id } => fetch_pot_stats(deps, campaign_id),
+        QueryMsg::VerifyReport { campaign_id, report, to_claim, claimant } => dry_run_claim(deps, env, campaign_id, report, to_claim, claimant),
+        QueryMsg::Roles {} => fetch_roles(deps),
+        QueryMsg::PauseStatus {} => fetch_pause_status(deps, env),
+        QueryMsg::FetchCampaigns { start_after, limit } => fetch_campaigns(deps, start_after, limit),
+        QueryMsg::IsAttested { address, requirements } => fetch_is_attested(deps, env, address, requirements),
+        #[cfg(feature = "private-queries")]
+        QueryMsg::MyClaims { address, key, start_after, limit } => fetch_with_viewing_key(deps, env, address, key, QueryWithPermit::MyClaims { start_after, limit }),
+        #[cfg(feature = "private-queries")]
+        QueryMsg::MyDonations { address, key, campaign_id } => fetch_with_viewing_key(deps, env, address, key, QueryWithPermit::MyDonations { campaign_id }),
+        #[cfg(feature = "private-queries")]
+        QueryMsg::MyReward { address, key, campaign_id } => fetch_with_viewing_key(deps, env, address, key, QueryWithPermit::MyReward { campaign_id }),
+        #[cfg(feature = "private-queries")]
+        QueryMsg::WithPermit { permit, query } => fetch_with_permit(deps, env, permit, query),
     }
 }
 
-fn fetch_seen_group_ids(deps: Deps, page: u64, page_size: u64) -> StdResult<Binary> {
-    let seen_group_ids: Vec<_> = SEEN_GROUP_IDS.range(deps.storage, None, None, Order::Ascending).skip((page * page_size) as usize).take(page_size as usize).map(|x| x.unwrap()).collect();
+fn fetch_campaign(deps: Deps, campaign_id: u64) -> StdResult<Binary> {
+    let (campaign, pot) = load_campaign(deps.storage, campaign_id)?;
 
-    let mut actual_geids: Vec<u32> = Vec::new();
+    to_json_binary(&CampaignResponse { campaign_id, campaign, pot: pot.for_response() })
+}
 
-    for group_pair in seen_group_ids {
-        let group_id = group_pair.0;
+/// Runs every check a claim would go through without touching the state, so operators know whether a report is
+/// accepted before they spend gas on it.
+fn dry_run_claim(deps: Deps, env: Env, campaign_id: u64, report: IASReport, to_claim: ClaimStruct, claimant: String) -> StdResult<Binary> {
+    let (campaign, pot_data) = load_campaign(deps.storage, campaign_id)?;
+    let claimant = deps.api.addr_canonicalize(deps.api.addr_validate(&claimant)?.as_str())?;
+
+    let signing_key = load_signing_key(deps.storage)?;
+    let mut verification = verify_report_with_key(&report, &to_claim, &campaign.policy, &signing_key.modulus, &signing_key.exponent);
+    verification.checks.insert(0, ReportCheck { check: "campaign_open".to_string(), passed: true, reason: None });
+    if let Err(error) = ensure_claims_not_paused(deps.storage, &env).and_then(|_| ensure_taking_claims(&env, &campaign, &pot_data)) {
+        verification.checks[0] = ReportCheck { check: "campaign_open".to_string(), passed: false, reason: Some(error.to_string()) };
+    }
 
-        ensure!(group_id.len() == 4, StdError::generic_err("The group id that was previously submitted didn't work"));
+    verification.record("claimant", ensure_claim_is_for(&to_claim, &claimant));
 
+    // The group id is only known when the platform info blob parsed
+    let group_id = verification.platform_info.map(|pib| pib.group_id());
+    let group_id_outcome = match group_id {
+        Some(group_id) => ensure_group_id_unseen(deps.storage, campaign_id, group_id),
+        None => Err(ContractError::InvalidPlatformInfoBlob {}),
+    };
+    verification.record("group_id", group_id_outcome);
 
-        let mut container: [u8; 4] = [0, 0, 0 ,0];
+    let bonus_tier = verification.platform_info
+        .and_then(|pib| campaign.policy.bonus_tier(&report.report.isv_enclave_quote_status, pib.cpusvn_is_up_to_date()));
 
-        for group_id_i in 0..group_id.len() {
-            let group_byte = group_id[group_id_i];
+    // Only an accepted report gets a reward, and only if the pot still has reward units to hand out
+    let reward = match verification.first_failure() {
+        Some(_) => None,
+        None => verification.record("reward", pot_data.preview_claim(deps.storage, &claimant, bonus_tier)),
+    };
 
-            container[group_id_i] = group_byte;
-        }
+    to_json_binary(&VerifyReportResponse {
+        accepted: verification.first_failure().is_none(),
+        checks: verification.checks,
+        bonus_tier: bonus_tier.map(|tier| tier.name.clone()),
+        reward,
+    })
+}
 
-        let group_id_int = u32::from_be_bytes(container);
+fn fetch_pot_stats(deps: Deps, campaign_id: u64) -> StdResult<Binary> {
+    let (campaign, pot) = load_campaign(deps.storage, campaign_id)?;
+    let signing_key = load_signing_key(deps.storage)?;
+
+    to_json_binary(&PotStatsResponse {
+        campaign_id,
+        total_donations: Payout { native: pot.total_donations, cw20: pot.cw20_total_donations.clone() },
+        pot_of_rewards: Payout { native: pot.pot_of_rewards, cw20: pot.cw20_pot_of_rewards.clone() },
+        remaining_reward_units: pot.remaining_reward_units(),
+        total_paid_out: pot.total_paid_out,
+        claims: pot.GEIDs,
+        unique_donors: pot.unique_donors,
+        total_reward_units: pot.total_reward_units,
+        reward_curve: pot.reward_curve,
+        policy: campaign.policy,
+        signing_key_modulus: signing_key.modulus,
+        signing_key_exponent: signing_key.exponent,
+    })
+}
 
-        actual_geids.push(group_id_int);
-    }
+fn fetch_roles(deps: Deps) -> StdResult<Binary> {
+    let ownership = OWNERSHIP.load(deps.storage)?;
+
+    to_json_binary(&RolesResponse {
+        owner: ownership.owner,
+        pending_owner: ownership.pending_owner,
+        policy_admins: role_holders(deps.storage, Role::PolicyAdmin)?,
+        revokers: role_holders(deps.storage, Role::Revoker)?,
+        pausers: role_holders(deps.storage, Role::Pauser)?,
+    })
+}
+
+fn fetch_pause_status(deps: Deps, env: Env) -> StdResult<Binary> {
+    let pause = PAUSE.may_load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+
+    to_json_binary(&PauseResponse {
+        claims_paused: pause.as_ref().is_some_and(|pause| pause.claims_paused(current_time)),
+        donations_paused: pause.as_ref().is_some_and(|pause| pause.donations_paused(current_time)),
+        pause,
+    })
+}
+
+fn fetch_campaigns(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+
+    let campaigns = CAMPAIGNS.range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|campaign| {
+            let (campaign_id, campaign) = campaign?;
+            let pot = REWARD_POTS.load(deps.storage, campaign_id)?;
+            Ok(CampaignResponse { campaign_id, campaign, pot: pot.for_response() })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let next_start_after = next_cursor(&campaigns, limit, |campaign| campaign.campaign_id);
+
+    to_json_binary(&FetchCampaignsResponse { campaigns, next_start_after })
+}
+
+fn fetch_donations(deps: Deps, env: Env, campaign_id: u64, donor: String) -> StdResult<Binary> {
+    let (_, pot_data) = load_campaign(deps.storage, campaign_id)?;
+    let canonicalized_donor = deps.api.addr_canonicalize(deps.api.addr_validate(&donor)?.as_str())?;
+
+    let donations = DONATION_ENTRIES
+        .prefix((campaign_id, Vec::from(canonicalized_donor.as_slice())))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|entry| {
+            let (donation_id, entry) = entry?;
+            let remaining = entry.remaining(&pot_data);
+            Ok(DonationEntryInfo {
+                donation_id,
+                remaining,
+                consumed: entry.amount - remaining,
+                withdrawable: entry.is_withdrawable(&pot_data, env.block.time.seconds()),
+                entry,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_json_binary(&FetchDonationsResponse { donations })
+}
+
+fn fetch_donor(deps: Deps, campaign_id: u64, address: String) -> StdResult<Binary> {
+    let donor = deps.api.addr_canonicalize(deps.api.addr_validate(&address)?.as_str())?;
+    let donation_info = DONATIONS.may_load(deps.storage, (campaign_id, donor.to_vec()))?;
+
+    to_json_binary(&DonorResponse { donation_info })
+}
+
+fn fetch_claimant(deps: Deps, campaign_id: u64, address: String) -> StdResult<Binary> {
+    let claimant = deps.api.addr_canonicalize(deps.api.addr_validate(&address)?.as_str())?;
+    let reward_info = REWARDED.may_load(deps.storage, (campaign_id, claimant.to_vec()))?;
+
+    to_json_binary(&ClaimantResponse { reward_info })
+}
+
+fn fetch_is_attested(deps: Deps, env: Env, address: String, requirements: AttestationRequirements) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let attestation = find_attestation(deps.storage, &address, &requirements, env.block.time.seconds())?;
+
+    let attested = attestation.is_some();
+    // The private-queries build only answers yes or no, the details would tell everyone which enclave an address runs
+    // and when
+    let attestation = attestation.filter(|_| cfg!(not(feature = "private-queries"))).map(|attestation| AttestedEnclave {
+        mrenclave: attestation.mrenclave,
+        quote_status: attestation.quote_status,
+        attested_at: attestation.attested_at,
+        expires_at: attestation.expires_at,
+    });
+
+    to_json_binary(&IsAttestedResponse { attested, attestation })
+}
+
+fn fetch_group_id(deps: Deps, campaign_id: u64, gid: u32) -> StdResult<Binary> {
+    let claimant = SEEN_GROUP_IDS.may_load(deps.storage, (campaign_id, gid))?;
+
+    to_json_binary(&GroupIdResponse { gid, gid_hex: group_id_hex(gid), claimant })
+}
+
+fn fetch_seen_group_ids(deps: Deps, campaign_id: u64, start_after: Option<u32>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
 
-    let response = FetchSeenGroupIds {
-        rewarded: actual_geids,
+    let seen_group_ids = SEEN_GROUP_IDS.prefix(campaign_id)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|seen| {
+            let (gid, claimant) = seen?;
+            Ok(SeenGroupId { gid, gid_hex: group_id_hex(gid), claimant })
+        })
+        .collect::<StdResult<Vec<SeenGroupId>>>()?;
+
+    let next_start_after = next_cursor(&seen_group_ids, limit, |seen| seen.gid);
+
+    to_json_binary(&FetchSeenGroupIds { rewarded: seen_group_ids, next_start_after })
+}
+
+fn fetch_claimants(deps: Deps, campaign_id: u64, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+    let start = canonical_bound(deps, start_after)?;
+
+    let rewarded = REWARDED.prefix(campaign_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|claimant| {
+            let (claimant, reward_info) = claimant?;
+            Ok((deps.api.addr_humanize(&CanonicalAddr::from(claimant))?, reward_info))
+        })
+        .collect::<StdResult<Vec<(Addr, RewardInfo)>>>()?;
+
+    let next_start_after = next_cursor(&rewarded, limit, |(claimant, _)| claimant.to_string());
+
+    to_json_binary(&FetchRewardedResponse { rewarded, next_start_after })
+}
+
+fn fetch_donors(deps: Deps, campaign_id: u64, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+    let start = canonical_bound(deps, start_after)?;
+
+    let donors = DONATIONS.prefix(campaign_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|donor| {
+            let (donor, donation_info) = donor?;
+            Ok((deps.api.addr_humanize(&CanonicalAddr::from(donor))?, donation_info))
+        })
+        .collect::<StdResult<Vec<(Addr, DonationInfo)>>>()?;
+
+    let next_start_after = next_cursor(&donors, limit, |(donor, _)| donor.to_string());
+
+    to_json_binary(&FetchDonorsResponse { donors, next_start_after })
+}
+
+fn fetch_claims_by_address(deps: Deps, address: String, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+    let claimant = deps.api.addr_validate(&address)?;
+
+    let claims = CLAIMS.idx.claimant
+        .prefix(claimant)
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, ClaimRecord)>>>()?;
+
+    let next_start_after = next_cursor(&claims, limit, |(claim_id, _)| *claim_id);
+
+    to_json_binary(&ClaimsResponse { claims, next_start_after })
+}
+
+/// The queries that tell who donated or claimed, or with which group id. The private-queries build only tells donors and
+/// claimants about themselves.
+#[cfg(feature = "private-queries")]
+fn ensure_not_private(msg: &QueryMsg) -> StdResult<()> {
+    let donors = matches!(msg,
+        QueryMsg::FetchDonors { .. } | QueryMsg::TopDonors { .. } | QueryMsg::Donor { .. } | QueryMsg::FetchDonations { .. });
+    ensure!(!donors, StdError::generic_err("Donors are private on this contract, PotStats has the totals and donors see their own donations with MyDonations or a permit"));
+    let claimants = matches!(msg,
+        QueryMsg::FetchClaimants { .. } | QueryMsg::FetchSeenGuids { .. } | QueryMsg::Claimant { .. } | QueryMsg::GroupId { .. }
+        | QueryMsg::ClaimsByAddress { .. } | QueryMsg::ClaimsInRange { .. });
+    ensure!(!claimants, StdError::generic_err("Claimants are private on this contract, PotStats has the totals and claimants see their own claims with MyClaims or a permit"));
+
+    Ok(())
+}
+
+#[cfg(feature = "private-queries")]
+fn fetch_with_viewing_key(deps: Deps, env: Env, address: String, key: String, query: QueryWithPermit) -> StdResult<Binary> {
+    let owner = deps.api.addr_validate(&address)?;
+    check_viewing_key(deps.storage, &owner, &key)?;
+
+    fetch_private(deps, env, owner, query)
+}
+
+#[cfg(feature = "private-queries")]
+fn fetch_with_permit(deps: Deps, env: Env, permit: Permit, query: QueryWithPermit) -> StdResult<Binary> {
+    let owner = permit.validate(deps, &env, query.permission())?;
+
+    fetch_private(deps, env, owner, query)
+}
+
+/// Answers a query about someone who proved it's them, padded so its length doesn't tell how much they did
+#[cfg(feature = "private-queries")]
+fn fetch_private(deps: Deps, env: Env, owner: Addr, query: QueryWithPermit) -> StdResult<Binary> {
+    let response = match query {
+        QueryWithPermit::MyClaims { start_after, limit } => fetch_claims_by_address(deps, owner.into_string(), start_after, limit),
+        QueryWithPermit::MyDonations { campaign_id } => fetch_donations(deps, env, campaign_id, owner.into_string()),
+        QueryWithPermit::MyReward { campaign_id } => fetch_claimant(deps, campaign_id, owner.into_string()),
+    }?;
+
+    Ok(pad_query_response(response))
+}
+
+fn fetch_claims_in_range(deps: Deps, start_time: u64, end_time: u64, start_after: Option<u64>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+
+    // The index is ordered by (claim time, claim id), so we need the claim's time to start after it
+    let min = match start_after {
+        Some(claim_id) => Bound::exclusive((CLAIMS.load(deps.storage, claim_id)?.claim_time, claim_id)),
+        None => Bound::inclusive((start_time, 0)),
     };
+    // Claim IDs start at zero, so this leaves out every claim made at the end time
+    let max = Bound::exclusive((end_time, 0));
+
+    let claims = CLAIMS.idx.claim_time
+        .range(deps.storage, Some(min), Some(max), Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<(u64, ClaimRecord)>>>()?;
 
-    let ser_response = to_json_binary(&response).unwrap();
+    let next_start_after = next_cursor(&claims, limit, |(claim_id, _)| *claim_id);
 
-    Ok(ser_response)
+    to_json_binary(&ClaimsResponse { claims, next_start_after })
 }
 
-fn fetch_claimants(deps: Deps, page: u64, page_size: u64) -> StdResult<Binary> {
-    let rewarded: Vec<(Vec<u8>, RewardInfo)> = REWARDED.range(deps.storage, None, None, Order::Ascending).skip((page * page_size) as usize).take(page_size as usize).map(|x| x.unwrap()).collect();
+fn fetch_top_donors(deps: Deps, campaign_id: u64, start_after: Option<String>, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = page_limit(limit);
+    let start_after = start_after
+        .map(|donor| deps.api.addr_canonicalize(deps.api.addr_validate(&donor)?.as_str()))
+        .transpose()?;
 
-    let rewarded: Vec<(Addr, RewardInfo)> = rewarded.iter().map(|x| (deps.api.addr_humanize(&CanonicalAddr::from(x.0.clone())).unwrap(), x.1.clone())).collect();
+    let donors = top_donors(deps.storage, campaign_id, start_after.as_ref(), limit)?
+        .into_iter()
+        .map(|(donor, donation_info)| Ok((deps.api.addr_humanize(&CanonicalAddr::from(donor))?, donation_info)))
+        .collect::<StdResult<Vec<(Addr, DonationInfo)>>>()?;
 
-    let rewarded_info: FetchRewardedResponse = FetchRewardedResponse { rewarded };
-    
-    let ser_rewarded = to_json_binary(&rewarded_info).unwrap();
+    let next_start_after = next_cursor(&donors, limit, |(donor, _)| donor.to_string());
 
-    Ok(ser_rewarded)
+    to_json_binary(&FetchDonorsResponse { donors, next_start_after })
 }
 
-fn fetch_donors(deps: Deps, page: u64, page_size: u64) -> StdResult<Binary> {
-    let donors: Vec<_> = DONATIONS.range(deps.storage, None, None, Order::Ascending).skip((page * page_size) as usize).map(|x| x.unwrap()).take(page_size as usize).collect();
+/// The number of entries a list query returns when no limit is given
+const DEFAULT_PAGE_LIMIT: u32 = 10;
+/// The most entries a list query returns, so that a page never runs out of query gas
+const MAX_PAGE_LIMIT: u32 = 30;
 
-    let donors: Vec<(Addr, DonationInfo)> = donors.iter().map(|x| (deps.api.addr_humanize(&CanonicalAddr::from(x.0.clone())).unwrap(), x.1.clone())).collect();
-    
-    let donation_info: FetchDonorsResponse = FetchDonorsResponse { donors };
+fn page_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize
+}
 
-    let ser_donors = to_json_binary(&donation_info).unwrap();
+/// Addresses are stored canonicalized, so that is what we have to start after
+fn canonical_bound(deps: Deps, start_after: Option<String>) -> StdResult<Option<Bound<'static, Vec<u8>>>> {
+    start_after
+        .map(|address| {
+            let canonical = deps.api.addr_canonicalize(deps.api.addr_validate(&address)?.as_str())?;
+            Ok(Bound::exclusive(canonical.to_vec()))
+        })
+        .transpose()
+}
 
-    Ok(ser_donors)
+/// A full page means there may be more to fetch, so the last entry is where the next page starts after
+fn next_cursor<T, C>(page: &[T], limit: usize, cursor: impl Fn(&T) -> C) -> Option<C> {
+    if page.len() == limit {
+        page.last().map(cursor)
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
+// The private-queries build leaves out the tests of the public claimant queries, and with them some of what they use
+#[cfg_attr(feature = "private-queries", allow(unused_imports))]
 mod tests {
-    use cosmwasm_std::{Addr, Api, CanonicalAddr, Order, StdResult, to_json_binary};
-    use cosmwasm_std::testing::mock_dependencies;
-    use crate::msg::FetchDonorsResponse;
-    use crate::state::{DonationInfo, DONATIONS};
+    use cosmwasm_std::{Addr, Api, BankMsg, CanonicalAddr, Coin, coins, CosmosMsg, from_json, Order, OwnedDeps, to_json_binary, Uint128, Uint256, WasmMsg};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockQuerier, MockStorage};
+    use cw20::Cw20ExecuteMsg;
+    use cw2::{get_contract_version, set_contract_version};
+    use crate::ContractError;
+    use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION, ensure_group_id_unseen, execute, instantiate, migrate, NATIVE_DENOM, payout_messages, query};
+    use crate::fixtures::{Bech32Api, bech32_dependencies, CLAIM, CLAIMANT, IAS_RESPONSE};
+    use crate::intelstructs::{ClaimStruct, IASReport};
+    use crate::msg::{ClaimsResponse, ExecuteMsg, FetchDonorsResponse, InstantiateMsg, MigrateMsg, QueryMsg, VerifyReportResponse};
+    use crate::state::{ClaimRecord, DonationInfo, DONATIONS, Payout, save_claim_record, SEEN_GROUP_IDS, TokenBalance};
+
+    #[test]
+    fn test_migrate_records_the_version() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        let response = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: Some("owner".to_string()) }).unwrap();
+
+        assert_eq!(response.attributes[0].value, "0.1.0");
+        assert_eq!(response.attributes[1].value, CONTRACT_VERSION);
+        assert_eq!(get_contract_version(deps.as_ref().storage).unwrap().version, CONTRACT_VERSION);
+
+        // Migrating to the same version again runs nothing
+        let response = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert_eq!(response.attributes[2].value, "");
+    }
+
+    #[test]
+    fn test_migrate_refuses_downgrades_and_other_contracts() {
+        let mut deps = mock_dependencies();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let error = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(error.to_string().contains("down to"));
+
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let error = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert!(error.to_string().contains("different contract"));
+    }
+
+    #[test]
+    fn test_group_ids_are_claimed_once_per_campaign() {
+        let mut deps = mock_dependencies();
+        SEEN_GROUP_IDS.save(deps.as_mut().storage, (0, 0x0CB0), &Addr::unchecked("alice")).unwrap();
+
+        let error = ensure_group_id_unseen(deps.as_ref().storage, 0, 0x0CB0).unwrap_err();
+        assert_eq!(error, ContractError::GroupAlreadyClaimed { gid: 0x0CB0, claimant: Addr::unchecked("alice") });
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(error.to_string(), "Group ID : 00000CB0 has already been claimed by alice");
+        #[cfg(feature = "private-queries")]
+        assert_eq!(error.to_string(), "Group ID : 00000CB0 has already been claimed");
+
+        ensure_group_id_unseen(deps.as_ref().storage, 1, 0x0CB0).unwrap();
+    }
+
+    /// A contract that trusts Intel's key, with chain addresses and a donation in its default campaign
+    fn genuine_deps() -> OwnedDeps<MockStorage, Bech32Api, MockQuerier> {
+        let mut deps = bech32_dependencies();
+        let donor = Bech32Api::address(&[7; 20]);
+        let instantiate_msg = InstantiateMsg { total_reward_shares: Uint256::from(10u128), admin: None, owner: None, signing_key: None, attestation_ttl: None };
+        instantiate(deps.as_mut(), mock_env(), mock_info(&donor, &[]), instantiate_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&donor, &coins(100, NATIVE_DENOM)), ExecuteMsg::DonateToPot { campaign_id: 0, expires_at: None }).unwrap();
+
+        deps
+    }
+
+    #[test]
+    fn test_genuine_reports_are_claimed_with() {
+        let mut deps = genuine_deps();
+
+        // The report a real enclave got from IAS, checked against Intel's key
+        let claim = ExecuteMsg::ClaimGEID {
+            campaign_id: 0,
+            report: serde_json::from_str::<IASReport>(IAS_RESPONSE).unwrap(),
+            to_claim: serde_json::from_str::<ClaimStruct>(CLAIM).unwrap(),
+        };
+        let response = execute(deps.as_mut(), mock_env(), mock_info(CLAIMANT, &[]), claim).unwrap();
+        assert!(matches!(&response.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address, .. }) if to_address == CLAIMANT));
+    }
+
+    #[test]
+    fn test_genuine_reports_pass_the_dry_run() {
+        let deps = genuine_deps();
+        let verify = |claimant: &str| QueryMsg::VerifyReport {
+            campaign_id: 0,
+            report: serde_json::from_str::<IASReport>(IAS_RESPONSE).unwrap(),
+            to_claim: serde_json::from_str::<ClaimStruct>(CLAIM).unwrap(),
+            claimant: claimant.to_string(),
+        };
+
+        let verdict: VerifyReportResponse = from_json(query(deps.as_ref(), mock_env(), verify(CLAIMANT)).unwrap()).unwrap();
+        assert!(verdict.accepted, "{:?}", verdict.checks);
+        assert!(verdict.checks.iter().all(|check| check.passed));
+        assert!(verdict.reward.is_some());
+
+        // The same report for anyone else is refused for that alone
+        let verdict: VerifyReportResponse = from_json(query(deps.as_ref(), mock_env(), verify(&Bech32Api::address(&[8; 20]))).unwrap()).unwrap();
+        let failed: Vec<&str> = verdict.checks.iter().filter(|check| !check.passed).map(|check| check.check.as_str()).collect();
+        assert_eq!(failed, vec!["claimant"]);
+    }
+
+    #[test]
+    fn test_payout_messages() {
+        let claimant = Addr::unchecked("claimant");
+        let payout = Payout {
+            native: Uint256::from(5u128),
+            cw20: vec![
+                TokenBalance { token: Addr::unchecked("token_a"), amount: Uint256::from(7u128) },
+                TokenBalance { token: Addr::unchecked("token_b"), amount: Uint256::zero() },
+            ],
+        };
+
+        let messages = payout_messages(&claimant, &payout).unwrap();
+
+        assert_eq!(messages, vec![
+            CosmosMsg::Bank(BankMsg::Send { to_address: "claimant".to_string(), amount: vec![Coin::new(5, NATIVE_DENOM)] }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "token_a".to_string(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer { recipient: "claimant".to_string(), amount: Uint128::new(7) }).unwrap(),
+                funds: vec![],
+            }),
+        ]);
+
+        // Nothing to hand out means no messages, the bank module would refuse an empty send
+        let empty = Payout { native: Uint256::zero(), cw20: vec![] };
+        assert!(payout_messages(&claimant, &empty).unwrap().is_empty());
+    }
 
     #[test]
     fn test_max_reward_shares() {
         let mock_deps = mock_dependencies();
         let mut storage = mock_deps.storage;
-        let to_save = &[(Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 48]), DonationInfo { donation_size: Default::default(), last_donation: 0 }), (Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 49]), DonationInfo { donation_size: Default::default(), last_donation: 0 }), (Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 50]), DonationInfo { donation_size: Default::default(), last_donation: 0 })];
+        let to_save = &[(Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 48]), DonationInfo { donation_size: Default::default(), last_donation: 0, cw20_donations: vec![] }), (Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 49]), DonationInfo { donation_size: Default::default(), last_donation: 0, cw20_donations: vec![] }), (Vec::from([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 106, 49, 117, 103, 118, 118, 102, 56, 101, 48, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 117, 108, 121, 110, 100, 117, 118, 57, 120, 57, 52, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 114, 50, 101, 107, 99, 103, 117, 112, 57, 114, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 111, 99, 56, 121, 112, 55, 54, 54, 99, 50]), DonationInfo { donation_size: Default::default(), last_donation: 0, cw20_donations: vec![] })];
 
         for save in to_save {
-            DONATIONS.save(&mut storage, save.0.clone(), &save.1).unwrap();
-            println!("[!] Saved a new record in the database.")
+            DONATIONS.save(&mut storage, (0, save.0.clone()), &save.1).unwrap();
         }
 
         let page = 0u64;
         let page_size = 2u64;
 
-        // let donors = DONATIONS.range(&storage, None, None, Order::Ascending).take(page_size as usize).collect::<Vec<StdResult<(Vec<u8>, DonationInfo)>>>();
-
-        // println!("{:?}", donors);
+        let donors: Vec<(Vec<u8>, DonationInfo)> = DONATIONS.prefix(0).range(&storage, None, None, Order::Ascending).skip((page * page_size) as usize).take(page_size as usize).map(|x| x.unwrap()).collect();
 
-        let donors: Vec<(Vec<u8>, DonationInfo)> = DONATIONS.range(&storage, None, None, Order::Ascending).skip((page * page_size) as usize).take(page_size as usize).map(|x| x.unwrap()).collect();
-
-        println!("{:?}", donors);
+        // The first page holds the first two donors in key order
+        assert_eq!(donors, to_save[..2].to_vec());
 
         let donors: Vec<_> = donors.iter().map(|x| (mock_deps.api.addr_humanize(&CanonicalAddr::from(x.0.clone())).unwrap(), x.1.clone())).collect();
 
-        println!("{:?}", donors);
-
-        let donation_info: FetchDonorsResponse = FetchDonorsResponse { donors };
+        let donation_info: FetchDonorsResponse = FetchDonorsResponse { donors, next_start_after: None };
 
         let ser_donors = to_json_binary(&donation_info).unwrap();
 
-
-        println!("{:?}", ser_donors)
+        assert_eq!(from_json::<FetchDonorsResponse>(&ser_donors).unwrap(), donation_info);
     }
 
     #[test]
     fn test_canonicalize_addr() {
-        let mock_deps = mock_dependencies();
-        let address = Addr::unchecked("juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r");
+        let deps = bech32_dependencies();
+        let address = Addr::unchecked(CLAIMANT);
+
+        let canonical_addr = deps.api.addr_canonicalize(address.as_str()).unwrap();
 
-        let canonical_addr = mock_deps.api.addr_canonicalize(address.as_str()).unwrap();
+        // The canonical form of a juno address is the 20 bytes its bech32 data part encodes
+        assert_eq!(canonical_addr.as_slice(), &[248, 241, 194, 40, 232, 158, 72, 198, 216, 44, 230, 60, 150, 35, 71, 47, 53, 147, 7, 15]);
+        assert_eq!(deps.api.addr_humanize(&canonical_addr).unwrap(), address);
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_claim_history_queries() {
+        let mut deps = mock_dependencies();
+
+        for (claimant, claim_time) in [("alice", 100), ("bob", 150), ("alice", 200), ("alice", 250)] {
+            save_claim_record(deps.as_mut().storage, &ClaimRecord {
+                campaign_id: 0,
+                group_id: claim_time as u32,
+                claimant: Addr::unchecked(claimant),
+                payout: Payout { native: Uint256::from(10u128), cw20: vec![] },
+                denom: NATIVE_DENOM.to_string(),
+                quote_status: "OK".to_string(),
+                report_id: "1".to_string(),
+                bonus_tier: None,
+                block_height: 1,
+                claim_time,
+            }).unwrap();
+        }
 
-        println!("{:?}", canonical_addr)
+        let by_address = |start_after| -> ClaimsResponse {
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::ClaimsByAddress { address: "alice".to_string(), start_after, limit: Some(2) }).unwrap()).unwrap()
+        };
+        let first_page = by_address(None);
+        assert_eq!(first_page.claims.iter().map(|(claim_id, _)| *claim_id).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(first_page.next_start_after, Some(2));
+        let second_page = by_address(first_page.next_start_after);
+        assert_eq!(second_page.claims.iter().map(|(claim_id, _)| *claim_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(second_page.next_start_after, None);
+
+        let in_range = |start_after| -> ClaimsResponse {
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::ClaimsInRange { start_time: 150, end_time: 250, start_after, limit: Some(1) }).unwrap()).unwrap()
+        };
+        let first_page = in_range(None);
+        assert_eq!(first_page.claims[0].1.claimant, Addr::unchecked("bob"));
+        let second_page = in_range(first_page.next_start_after);
+        assert_eq!(second_page.claims[0].0, 2);
+        // The claim at the end time is left out
+        assert!(in_range(second_page.next_start_after).claims.is_empty());
     }
 }
@@ -1,13 +1,51 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Addr, StdError};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
     #[error("Unauthorized")]
     Unauthorized {},
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+
+    /// A field of the report that should hold base64 doesn't
+    #[error("The report's {field} isn't valid base64")]
+    InvalidBase64 { field: String },
+
+    #[error("The report's platformInfoBlob isn't a valid platform info blob")]
+    InvalidPlatformInfoBlob {},
+
+    /// The quote body decoded but is too short to hold the part we're after
+    #[error("The quote body is too short to hold its {part}")]
+    InvalidQuoteBody { part: String },
+
+    #[error("The report's timestamp {timestamp} isn't an IAS timestamp")]
+    InvalidTimestamp { timestamp: String },
+
+    #[error("The campaign doesn't accept reports with quote status {status}")]
+    QuoteStatusNotAccepted { status: String },
+
+    #[error("The campaign doesn't accept reports from enclave {mrenclave}")]
+    MrenclaveNotAccepted { mrenclave: String },
+
+    /// The report's signature isn't Intel's signature of the report
+    #[error("The recovered digest doesn't match the submitted Report's digest")]
+    SignatureMismatch {},
+
+    /// The report doesn't carry the claim that was submitted with it
+    #[error("Payload and ClaimStruct do not have matching content.")]
+    PayloadMismatch {},
+
+    /// The claim the report carries is for another address than the one that submitted it
+    #[error("The report's claim is for another address")]
+    ClaimantMismatch {},
+
+    // Who claimed with a group id is private on the private-queries build, the error would tell whoever tries it again
+    #[cfg_attr(not(feature = "private-queries"), error("Group ID : {gid:08X} has already been claimed by {claimant}"))]
+    #[cfg_attr(feature = "private-queries", error("Group ID : {gid:08X} has already been claimed"))]
+    GroupAlreadyClaimed { gid: u32, claimant: Addr },
+
+    #[error("Your generous donation has been noted :) Thank you! But nothing was sent along with it")]
+    NoFunds {},
 }
@@ -0,0 +1,212 @@
+use cosmwasm_std::{Addr, Decimal256, Event};
+#[cfg(not(feature = "private-queries"))]
+use cosmwasm_std::HexBinary;
+#[cfg(not(feature = "private-queries"))]
+use sha2::{Digest, Sha256};
+
+use crate::contract::NATIVE_DENOM;
+#[cfg(not(feature = "private-queries"))]
+use crate::msg::group_id_hex;
+use crate::state::Payout;
+
+// Indexers read these events, so their types and attribute keys don't change between versions. New attributes can
+// be added, but existing ones are never renamed or removed. The chain puts "wasm-" in front of the types.
+
+/// Emitted for every claim that is paid out, as `wasm-claim`
+pub const CLAIM_EVENT: &str = "claim";
+/// Emitted for every native or CW20 donation, as `wasm-donate`
+pub const DONATE_EVENT: &str = "donate";
+
+/// Everything an indexer needs to know about a claim
+pub struct ClaimEvent<'a> {
+    pub campaign_id: u64,
+    pub claimant: &'a Addr,
+    pub group_id: u32,
+    /// The report's EPID pseudonym, only its hash ends up in the event
+    pub epid_pseudonym: &'a [u8],
+    pub quote_status: &'a str,
+    pub report_id: &'a str,
+    /// What the claimant was paid
+    pub payout: &'a Payout,
+    /// The reward shares the claim was paid out on
+    pub reward_shares: Decimal256,
+    pub bonus_tier: Option<&'a str>,
+    /// What is left in the pot after the claim
+    pub pot_balance: &'a Payout
+}
+
+impl ClaimEvent<'_> {
+    pub fn into_event(self) -> Event {
+        let event = Event::new(CLAIM_EVENT)
+            .add_attribute("action", "claim")
+            .add_attribute("campaign_id", self.campaign_id.to_string());
+        // The private-queries build is its own deployment that never tells who claimed or with which platform
+        #[cfg(not(feature = "private-queries"))]
+        let event = event
+            .add_attribute("claimant", self.claimant)
+            .add_attribute("gid", group_id_hex(self.group_id))
+            .add_attribute("epid_pseudonym_hash", HexBinary::from(Sha256::digest(self.epid_pseudonym).as_slice()).to_hex());
+        let event = event.add_attribute("quote_status", self.quote_status);
+        #[cfg(not(feature = "private-queries"))]
+        let event = event.add_attribute("report_id", self.report_id);
+
+        event
+            .add_attribute("amount", amounts(self.payout))
+            .add_attribute("reward_shares", self.reward_shares.to_string())
+            .add_attribute("bonus_tier", self.bonus_tier.unwrap_or("none"))
+            .add_attribute("pot_balance", amounts(self.pot_balance))
+    }
+}
+
+/// Everything an indexer needs to know about a donation
+pub struct DonateEvent<'a> {
+    pub campaign_id: u64,
+    pub donor: &'a Addr,
+    /// What was donated, native coins or a single CW20 token
+    pub donation: &'a Payout,
+    /// When the donation can be withdrawn, it is irrevocable when unset
+    pub expires_at: Option<u64>,
+    /// What is in the pot after the donation
+    pub pot_balance: &'a Payout
+}
+
+impl DonateEvent<'_> {
+    pub fn into_event(self) -> Event {
+        let event = Event::new(DONATE_EVENT)
+            .add_attribute("action", "donate")
+            .add_attribute("campaign_id", self.campaign_id.to_string());
+        // Donors are as private as claimants on the private-queries build
+        #[cfg(not(feature = "private-queries"))]
+        let event = event.add_attribute("donor", self.donor);
+
+        event
+            .add_attribute("amount", amounts(self.donation))
+            .add_attribute("expires_at", self.expires_at.map_or("never".to_string(), |expires_at| expires_at.to_string()))
+            .add_attribute("pot_balance", amounts(self.pot_balance))
+    }
+}
+
+/// Lists every amount in a payout with its denom, the way the bank module lists coins, e.g.
+/// `5ujuno,7cw20:juno1...`. CW20 tokens are listed under their contract address. Empty amounts are left out,
+/// an empty payout is `0ujuno` since attributes can't be empty.
+pub fn amounts(payout: &Payout) -> String {
+    let native = (!payout.native.is_zero()).then(|| format!("{}{}", payout.native, NATIVE_DENOM));
+    let cw20 = payout.cw20.iter()
+        .filter(|balance| !balance.amount.is_zero())
+        .map(|balance| format!("{}cw20:{}", balance.amount, balance.token));
+
+    let amounts = native.into_iter().chain(cw20).collect::<Vec<_>>();
+    if amounts.is_empty() {
+        return format!("0{}", NATIVE_DENOM);
+    }
+
+    amounts.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Attribute, Decimal256, Uint256};
+
+    use crate::events::{amounts, ClaimEvent, DonateEvent};
+    use crate::state::{Payout, TokenBalance};
+
+    fn attribute_keys(attributes: &[Attribute]) -> Vec<&str> {
+        attributes.iter().map(|attribute| attribute.key.as_str()).collect()
+    }
+
+    #[test]
+    #[cfg(not(feature = "private-queries"))]
+    fn test_claim_event_schema() {
+        let payout = Payout { native: Uint256::from(5u128), cw20: vec![TokenBalance { token: Addr::unchecked("token"), amount: Uint256::from(7u128) }] };
+        let pot_balance = Payout { native: Uint256::from(95u128), cw20: vec![] };
+
+        let event = ClaimEvent {
+            campaign_id: 3,
+            claimant: &Addr::unchecked("claimant"),
+            group_id: 0x0CB0,
+            epid_pseudonym: b"pseudonym",
+            quote_status: "OK",
+            report_id: "42",
+            payout: &payout,
+            reward_shares: Decimal256::percent(150),
+            bonus_tier: None,
+            pot_balance: &pot_balance,
+        }.into_event();
+
+        // Indexers depend on these, they are only ever added to
+        assert_eq!(event.ty, "claim");
+        assert_eq!(attribute_keys(&event.attributes), vec![
+            "action", "campaign_id", "claimant", "gid", "epid_pseudonym_hash", "quote_status", "report_id", "amount",
+            "reward_shares", "bonus_tier", "pot_balance",
+        ]);
+
+        let value = |key: &str| event.attributes.iter().find(|attribute| attribute.key == key).unwrap().value.clone();
+        assert_eq!(value("gid"), "00000CB0");
+        // The pseudonym itself stays out of the event, only its sha256 goes in
+        assert_eq!(value("epid_pseudonym_hash"), "70a011a20493ce402b5bdebba2018072d839215ea745789a5a14f11c71726783");
+        assert_eq!(value("amount"), "5ujuno,7cw20:token");
+        assert_eq!(value("reward_shares"), "1.5");
+        assert_eq!(value("bonus_tier"), "none");
+        assert_eq!(value("pot_balance"), "95ujuno");
+    }
+
+    #[test]
+    #[cfg(feature = "private-queries")]
+    fn test_private_queries_claim_event_keeps_the_claimant_private() {
+        let payout = Payout { native: Uint256::from(5u128), cw20: vec![] };
+
+        let event = ClaimEvent {
+            campaign_id: 3,
+            claimant: &Addr::unchecked("claimant"),
+            group_id: 0x0CB0,
+            epid_pseudonym: b"pseudonym",
+            quote_status: "OK",
+            report_id: "42",
+            payout: &payout,
+            reward_shares: Decimal256::one(),
+            bonus_tier: None,
+            pot_balance: &payout,
+        }.into_event();
+
+        assert_eq!(attribute_keys(&event.attributes), vec![
+            "action", "campaign_id", "quote_status", "amount", "reward_shares", "bonus_tier", "pot_balance",
+        ]);
+        assert!(event.attributes.iter().all(|attribute| !attribute.value.contains("claimant") && attribute.value != "00000CB0"));
+    }
+
+    #[test]
+    fn test_donate_event_schema() {
+        let donation = Payout { native: Uint256::zero(), cw20: vec![TokenBalance { token: Addr::unchecked("token"), amount: Uint256::from(7u128) }] };
+
+        let event = DonateEvent {
+            campaign_id: 0,
+            donor: &Addr::unchecked("donor"),
+            donation: &donation,
+            expires_at: None,
+            pot_balance: &donation,
+        }.into_event();
+
+        assert_eq!(event.ty, "donate");
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(attribute_keys(&event.attributes), vec!["action", "campaign_id", "donor", "amount", "expires_at", "pot_balance"]);
+        // Donors are private on the private-queries build
+        #[cfg(feature = "private-queries")]
+        assert_eq!(attribute_keys(&event.attributes), vec!["action", "campaign_id", "amount", "expires_at", "pot_balance"]);
+        let value = |key: &str| event.attributes.iter().find(|attribute| attribute.key == key).unwrap().value.clone();
+        assert_eq!(value("amount"), "7cw20:token");
+        assert_eq!(value("expires_at"), "never");
+    }
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(amounts(&Payout::default()), "0ujuno");
+        let payout = Payout {
+            native: Uint256::from(1u128),
+            cw20: vec![
+                TokenBalance { token: Addr::unchecked("a"), amount: Uint256::zero() },
+                TokenBalance { token: Addr::unchecked("b"), amount: Uint256::from(2u128) },
+            ],
+        };
+        assert_eq!(amounts(&payout), "1ujuno,2cw20:b");
+    }
+}
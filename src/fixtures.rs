@@ -0,0 +1,71 @@
+//! The report captured from a real enclave, shared by the tests that check it
+
+use std::marker::PhantomData;
+
+use bech32::{FromBase32, ToBase32, Variant};
+use cosmwasm_std::{Addr, Api, CanonicalAddr, OwnedDeps, RecoverPubkeyError, StdError, StdResult, VerificationError};
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+
+/// A real IAS response captured from an enclave that put the hash of `CLAIM` in its report data
+pub(crate) const IAS_RESPONSE: &str = r#"{"report":{"id":"200423264892184291776794534127952959503","timestamp":"2023-11-23T11:47:05.757595","version":4,"epidPseudonym":"+CUyIi74LPqS6M0NF7YrSxLqPdX3MKs6D6LIPqRG/ZEB4WmxZVvxAJwdwg/0m9cYnUUQguLnJotthX645lAogfJgO8Xg5/91lSegwyUKvHmKgtjOHX/YTbVe/wmgWiBdaL+KmarY0Je459Px/FqGLWLsAF7egPAJRd1Xn88Znrs=","advisoryURL":"https://security-center.intel.com","advisoryIDs":["INTEL-SA-00161","INTEL-SA-00219","INTEL-SA-00289","INTEL-SA-00334","INTEL-SA-00615"],"isvEnclaveQuoteStatus":"CONFIGURATION_AND_SW_HARDENING_NEEDED","platformInfoBlob":"150200650000080000141402040180070000000000000000000D00000C000000020000000000000CB07FA713992F17617F506072BA90D3794110D036E2293096E6BF758122D4E6BB68EE3F69B49BA232441025B331F3FA6E6AD1E70E5D8892E5F6565E5C9FCE9B2A24","isvEnclaveQuoteBody":"AgABALAMAAAPAA8AAAAAAFHK9aSLRQ1iSu/jKG0xSJQAAAAAAAAAAAAAAAAAAAAAFBQCBwGAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABQAAAAAAAAAHAAAAAAAAAOPC8qW4QNieBprK/8rbZRDvhmpz06nuVxAO1fhkbuS7AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAc8uUpEUEPvz8ZkFapjVh5WlWaLoAJM/f80T0EhGInHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAC9zI5dTO9V43CN3I5/OaESDnWs8hiIOaCM/QJA3Uk5oQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"},"reportsig":"VEd3XgpDOEeRzHpKDx61yBUr4t74Z/kQztmOFM4nkaF+muCZe2KoMd1men4R6fYJh4U1DHnrI0U/zym0N4g6olLBfQ1otxb67LV7N1ekSLQtaJw+iQxNfkrqzNSnle3eKi08GAWVIrMRFC0UooCMuUyZoIGXBsjLZ/Jq1dldus2LUBGM5KHhxhAUbbxAdrcc6NO211S3DRAAYkQYkoHMgLZwWm73TS9LLCT/8pFvkiTUXbHyHpVhnbGB9jnkMd6y22iFQrIiQ+LZKcHCuvD5I07oPQqCezCq/rMMCR/6WAcumapLScNm5zndIeWnN8KE+8EG698eCw3GTONiXoE4hw=="}"#;
+/// The claim the enclave that generated `IAS_RESPONSE` made, for juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r
+pub(crate) const CLAIM: &str = r#"{"address":[31,3,24,28,4,10,7,8,19,25,4,12,13,22,1,12,28,24,30,9,12,8,26,7,5,28,26,25,6,1,24,15],"message":"Hello world!"}"#;
+/// The MRENCLAVE of the enclave that generated `IAS_RESPONSE`
+pub(crate) const MRENCLAVE: &str = "e3c2f2a5b840d89e069acaffcadb6510ef866a73d3a9ee57100ed5f8646ee4bb";
+
+/// The account `CLAIM` is for
+pub(crate) const CLAIMANT: &str = "juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r";
+
+/// An API that handles addresses the way the chain does, as `juno` bech32 whose canonical form is the data part, so
+/// `CLAIMANT` can claim with the captured report. The mock API's addresses are just shuffled strings.
+#[derive(Default)]
+pub(crate) struct Bech32Api(MockApi);
+
+impl Bech32Api {
+    /// The `juno` address of an account
+    pub fn address(account: &[u8]) -> String {
+        bech32::encode("juno", account.to_base32(), Variant::Bech32).unwrap()
+    }
+}
+
+impl Api for Bech32Api {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human).and_then(|canonical| self.addr_humanize(&canonical))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        match bech32::decode(human) {
+            Ok((prefix, words, Variant::Bech32)) if prefix == "juno" => Ok(CanonicalAddr::from(Vec::<u8>::from_base32(&words).unwrap())),
+            _ => Err(StdError::generic_err(format!("{} isn't a juno address", human))),
+        }
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        Ok(Addr::unchecked(Bech32Api::address(canonical.as_slice())))
+    }
+
+    fn secp256k1_verify(&self, message_hash: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(&self, message_hash: &[u8], signature: &[u8], recovery_param: u8) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0.secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(&self, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(&self, messages: &[&[u8]], signatures: &[&[u8]], public_keys: &[&[u8]]) -> Result<bool, VerificationError> {
+        self.0.ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+/// Dependencies with the chain's addresses, for tests that claim with the captured report
+pub(crate) fn bech32_dependencies() -> OwnedDeps<MockStorage, Bech32Api, MockQuerier> {
+    OwnedDeps { storage: MockStorage::default(), api: Bech32Api::default(), querier: MockQuerier::default(), custom_query_type: PhantomData }
+}
@@ -1,9 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, CustomQuery, QuerierWrapper, StdResult, WasmMsg};
 
-use crate::msg::ExecuteMsg;
+use crate::msg::{ExecuteMsg, IsAttestedResponse, QueryMsg};
+use crate::state::AttestationRequirements;
 
 /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -24,4 +25,15 @@ impl CwTemplateContract {
         }
         .into())
     }
+
+    /// Whether `address` proved it runs an enclave on SGX hardware the way `requirements` want it to, for contracts
+    /// that only let attested addresses through, e.g. with the enclave they trust and a `max_age` of 30 days
+    pub fn is_attested<Q: CustomQuery>(&self, querier: &QuerierWrapper<Q>, address: impl Into<String>, requirements: AttestationRequirements) -> StdResult<bool> {
+        Ok(self.attestation(querier, address, requirements)?.attested)
+    }
+
+    /// The attestation `is_attested` goes by, to see what enclave it was made with and until when it counts
+    pub fn attestation<Q: CustomQuery>(&self, querier: &QuerierWrapper<Q>, address: impl Into<String>, requirements: AttestationRequirements) -> StdResult<IsAttestedResponse> {
+        querier.query_wasm_smart(self.addr(), &QueryMsg::IsAttested { address: address.into(), requirements })
+    }
 }
@@ -0,0 +1,1349 @@
+#[cfg(test)]
+// The private-queries build leaves out the tests of the public claimant queries, and with them some of what they use
+#[cfg_attr(feature = "private-queries", allow(unused_imports, dead_code))]
+mod tests {
+    use cosmwasm_std::{Addr, Api, coins, Decimal256, Empty, HexBinary, Timestamp, Uint128, Uint256};
+    use cosmwasm_std::testing::MockApi;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+
+    use crate::ContractError;
+    use crate::fixtures::{CLAIM, IAS_RESPONSE, MRENCLAVE};
+    use crate::helpers::CwTemplateContract;
+    use crate::intelstructs::{ClaimStruct, IASReport};
+    use crate::mock_ias::{MockIas, MockPlatform};
+    use crate::msg::{AttestedEnclave, CampaignResponse, ClaimantResponse, ClaimsResponse, CreateCampaignMsg, DonorResponse, ExecuteMsg, FetchCampaignsResponse, FetchDonationsResponse, FetchDonorsResponse, FetchSeenGroupIds, GroupIdResponse, InstantiateMsg, IsAttestedResponse, PauseResponse, PotStatsResponse, QueryMsg, ReceiveMsg, RolesResponse, VerifyReportResponse};
+    use crate::state::{AttestationRequirements, BonusTier, ClaimPolicy, RewardCurve, Role, TokenBalance};
+
+    /// A bare bones CW20 token, just enough to hold balances and `Send` tokens to the lotto contract.
+    mod mock_cw20 {
+        use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, to_json_binary, Uint128};
+        use cosmwasm_schema::cw_serde;
+        use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+        use cw_storage_plus::Map;
+
+        const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+
+        #[cw_serde]
+        pub struct InstantiateMsg {
+            pub balances: Vec<(String, Uint128)>,
+        }
+
+        pub fn instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+            for (holder, amount) in msg.balances {
+                BALANCES.save(deps.storage, &Addr::unchecked(holder), &amount)?;
+            }
+            Ok(Response::new())
+        }
+
+        fn move_tokens(deps: DepsMut, from: &Addr, to: &Addr, amount: Uint128) -> StdResult<()> {
+            BALANCES.update(deps.storage, from, |balance| balance.unwrap_or_default().checked_sub(amount).map_err(StdError::from))?;
+            BALANCES.update(deps.storage, to, |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) })?;
+            Ok(())
+        }
+
+        pub fn execute(deps: DepsMut, _env: Env, info: MessageInfo, msg: Cw20ExecuteMsg) -> StdResult<Response> {
+            match msg {
+                Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                    move_tokens(deps, &info.sender, &Addr::unchecked(recipient), amount)?;
+                    Ok(Response::new())
+                }
+                Cw20ExecuteMsg::Send { contract, amount, msg } => {
+                    move_tokens(deps, &info.sender, &Addr::unchecked(&contract), amount)?;
+                    let receive = Cw20ReceiveMsg { sender: info.sender.to_string(), amount, msg };
+                    Ok(Response::new().add_message(receive.into_cosmos_msg(contract)?))
+                }
+                _ => Err(StdError::generic_err("not supported by the mock token")),
+            }
+        }
+
+        pub fn query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+            match msg {
+                Cw20QueryMsg::Balance { address } => {
+                    let balance = BALANCES.may_load(deps.storage, &Addr::unchecked(address))?.unwrap_or_default();
+                    to_json_binary(&BalanceResponse { balance })
+                }
+                _ => Err(StdError::generic_err("not supported by the mock token")),
+            }
+        }
+    }
+
+
+    struct Suite {
+        app: App,
+        lotto: Addr,
+        token: Addr,
+    }
+
+    fn setup() -> Suite {
+        let mut app = App::new(|router, _, storage| {
+            router.bank.init_balance(storage, &Addr::unchecked("donor1"), coins(1000, "ujuno")).unwrap();
+            router.bank.init_balance(storage, &Addr::unchecked("donor2"), coins(1000, "ujuno")).unwrap();
+        });
+
+        let lotto_code = app.store_code(Box::new(ContractWrapper::new(crate::contract::execute, crate::contract::instantiate, crate::contract::query)));
+        let token_code = app.store_code(Box::new(ContractWrapper::<_, _, _, _, _, _, Empty>::new(mock_cw20::execute, mock_cw20::instantiate, mock_cw20::query)));
+
+        let token = app.instantiate_contract(
+            token_code,
+            Addr::unchecked("minter"),
+            &mock_cw20::InstantiateMsg { balances: vec![("donor1".to_string(), Uint128::new(1000)), ("donor2".to_string(), Uint128::new(500))] },
+            &[],
+            "token",
+            None,
+        ).unwrap();
+
+        let lotto = app.instantiate_contract(
+            lotto_code,
+            Addr::unchecked("creator"),
+            &InstantiateMsg { total_reward_shares: Uint256::from(10u128), admin: None, owner: None, signing_key: Some(MockIas::default().signing_key()), attestation_ttl: None },
+            &[],
+            "lotto",
+            None,
+        ).unwrap();
+
+        Suite { app, lotto, token }
+    }
+
+    fn donate_cw20(suite: &mut Suite, donor: &str, amount: u128) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let send = cw20::Cw20ExecuteMsg::Send {
+            contract: suite.lotto.to_string(),
+            amount: Uint128::new(amount),
+            msg: cosmwasm_std::to_json_binary(&ReceiveMsg::DonateToPot { campaign_id: 0, expires_at: None }).unwrap(),
+        };
+        suite.app.execute_contract(Addr::unchecked(donor), suite.token.clone(), &send, &[])
+    }
+
+    fn donate_native(suite: &mut Suite, donor: &str, amount: u128, expires_at: Option<u64>) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(donor), lotto, &ExecuteMsg::DonateToPot { campaign_id: 0, expires_at }, &coins(amount, "ujuno"))
+    }
+
+    fn withdraw(suite: &mut Suite, donor: &str) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(donor), lotto, &ExecuteMsg::WithdrawDonation { campaign_id: 0 }, &[])
+    }
+
+    /// A campaign that takes any report at any time, for the tests to tweak
+    fn sponsored_campaign() -> CreateCampaignMsg {
+        CreateCampaignMsg {
+            name: "sponsored".to_string(),
+            admin: None,
+            total_reward_units: Uint256::from(4u128),
+            reward_curve: RewardCurve::EvenSplit {},
+            accepted_quote_statuses: vec![],
+            accepted_mrenclaves: vec![],
+            bonus_tiers: vec![],
+            start_time: None,
+            end_time: None,
+            donation_cutoff: None,
+            treasury: None,
+        }
+    }
+
+    fn create_campaign(suite: &mut Suite, accepted_quote_statuses: Vec<String>, accepted_mrenclaves: Vec<HexBinary>, start_time: Option<u64>) -> u64 {
+        create_campaign_from(suite, CreateCampaignMsg { accepted_quote_statuses, accepted_mrenclaves, start_time, ..sponsored_campaign() })
+    }
+
+    fn create_campaign_from(suite: &mut Suite, create: CreateCampaignMsg) -> u64 {
+        let lotto = suite.lotto.clone();
+        let response = suite.app.execute_contract(Addr::unchecked("sponsor"), lotto, &ExecuteMsg::CreateCampaign(create), &[]).unwrap();
+
+        let campaign_id = response.events.iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attribute| attribute.key == "campaign_id")
+            .unwrap();
+        campaign_id.value.parse().unwrap()
+    }
+
+    fn claim(suite: &mut Suite, claimant: &str, campaign_id: u64) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let claim = ExecuteMsg::ClaimGEID {
+            campaign_id,
+            report: serde_json::from_str::<IASReport>(IAS_RESPONSE).unwrap(),
+            to_claim: serde_json::from_str::<ClaimStruct>(CLAIM).unwrap(),
+        };
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(claimant), lotto, &claim, &[])
+    }
+
+    fn native_balance(suite: &Suite, holder: &str) -> Uint128 {
+        suite.app.wrap().query_balance(holder, "ujuno").unwrap().amount
+    }
+
+    fn token_balance(suite: &Suite, holder: &Addr) -> Uint128 {
+        let response: cw20::BalanceResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.token, &cw20::Cw20QueryMsg::Balance { address: holder.to_string() })
+            .unwrap();
+        response.balance
+    }
+
+    #[test]
+    // Donors are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_cw20_donations_are_recorded() {
+        let mut suite = setup();
+
+        let response = donate_cw20(&mut suite, "donor1", 100).unwrap();
+        assert!(response.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("donation", "100")));
+        donate_cw20(&mut suite, "donor2", 50).unwrap();
+        donate_cw20(&mut suite, "donor1", 25).unwrap();
+
+        let lotto = suite.lotto.clone();
+        assert_eq!(token_balance(&suite, &lotto), Uint128::new(175));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("donor1")), Uint128::new(875));
+
+        let donors: FetchDonorsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::FetchDonors { campaign_id: 0, start_after: None, limit: None })
+            .unwrap();
+
+        assert_eq!(donors.donors.len(), 2);
+        for (donor, info) in donors.donors {
+            let expected = if donor == Addr::unchecked("donor1") { 125u128 } else { 50u128 };
+            assert_eq!(info.cw20_donations, vec![TokenBalance { token: suite.token.clone(), amount: Uint256::from(expected) }]);
+            assert_eq!(info.donation_size, Uint256::zero());
+        }
+    }
+
+    #[test]
+    fn test_cw20_donation_needs_a_known_hook() {
+        let mut suite = setup();
+
+        let send = cw20::Cw20ExecuteMsg::Send {
+            contract: suite.lotto.to_string(),
+            amount: Uint128::new(10),
+            msg: cosmwasm_std::to_json_binary(&"not a hook").unwrap(),
+        };
+        suite.app.execute_contract(Addr::unchecked("donor1"), suite.token.clone(), &send, &[]).unwrap_err();
+
+        // The failed send is rolled back, the donor keeps their tokens
+        assert_eq!(token_balance(&suite, &Addr::unchecked("donor1")), Uint128::new(1000));
+    }
+
+    #[test]
+    fn test_cw20_donation_of_nothing_is_refused() {
+        let mut suite = setup();
+
+        donate_cw20(&mut suite, "donor1", 0).unwrap_err();
+
+        // Calling the receive hook directly with a zero amount is refused as well
+        let receive = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "donor1".to_string(),
+            amount: Uint128::zero(),
+            msg: cosmwasm_std::to_json_binary(&ReceiveMsg::DonateToPot { campaign_id: 0, expires_at: None }).unwrap(),
+        });
+        let token = suite.token.clone();
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(token, lotto, &receive, &[]).unwrap_err();
+    }
+
+    #[test]
+    // Donors are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_locked_donation_is_withdrawable_after_expiry() {
+        let mut suite = setup();
+        let expires_at = suite.app.block_info().time.seconds() + 100;
+
+        donate_native(&mut suite, "donor1", 300, Some(expires_at)).unwrap();
+        donate_native(&mut suite, "donor1", 200, None).unwrap();
+        assert_eq!(native_balance(&suite, "donor1"), Uint128::new(500));
+
+        // Still locked
+        withdraw(&mut suite, "donor1").unwrap_err();
+
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+        // Only the locked donation comes back, the other one is irrevocable
+        withdraw(&mut suite, "donor1").unwrap();
+        assert_eq!(native_balance(&suite, "donor1"), Uint128::new(800));
+        assert_eq!(native_balance(&suite, suite.lotto.as_str()), Uint128::new(200));
+
+        let donations: FetchDonationsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::FetchDonations { campaign_id: 0, donor: "donor1".to_string() })
+            .unwrap();
+        assert_eq!(donations.donations.len(), 1);
+        assert_eq!(donations.donations[0].remaining, Uint256::from(200u128));
+        assert!(!donations.donations[0].withdrawable);
+
+        // Nothing is left to withdraw
+        withdraw(&mut suite, "donor1").unwrap_err();
+    }
+
+    #[test]
+    fn test_donation_expiry_must_be_in_the_future() {
+        let mut suite = setup();
+        let now = suite.app.block_info().time.seconds();
+
+        donate_native(&mut suite, "donor1", 300, Some(now)).unwrap_err();
+    }
+
+    #[test]
+    fn test_cancelled_campaign_refunds_every_donation() {
+        let mut suite = setup();
+
+        donate_native(&mut suite, "donor1", 300, None).unwrap();
+        let expires_at = suite.app.block_info().time.seconds() + 1000;
+        donate_native(&mut suite, "donor2", 100, Some(expires_at)).unwrap();
+        donate_cw20(&mut suite, "donor2", 50).unwrap();
+
+        // Only the admin can cancel
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id: 0 }, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id: 0 }, &[]).unwrap();
+
+        // No more donations are taken
+        donate_native(&mut suite, "donor1", 10, None).unwrap_err();
+
+        withdraw(&mut suite, "donor1").unwrap();
+        withdraw(&mut suite, "donor2").unwrap();
+
+        assert_eq!(native_balance(&suite, "donor1"), Uint128::new(1000));
+        assert_eq!(native_balance(&suite, "donor2"), Uint128::new(1000));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("donor2")), Uint128::new(500));
+        assert_eq!(native_balance(&suite, lotto.as_str()), Uint128::zero());
+    }
+
+    #[test]
+    // Donors are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_campaigns_have_their_own_pots() {
+        let mut suite = setup();
+        let campaign_id = create_campaign(&mut suite, vec![], vec![], None);
+        assert_eq!(campaign_id, 1);
+
+        donate_native(&mut suite, "donor1", 300, None).unwrap();
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(120, "ujuno")).unwrap();
+
+        // There is no campaign 7 to donate to
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id: 7, expires_at: None }, &coins(120, "ujuno")).unwrap_err();
+
+        let donors: FetchDonorsResponse = suite.app.wrap()
+            .query_wasm_smart(&lotto, &QueryMsg::FetchDonors { campaign_id, start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(donors.donors.len(), 1);
+        assert_eq!(donors.donors[0].0, Addr::unchecked("donor2"));
+
+        let campaign: CampaignResponse = suite.app.wrap()
+            .query_wasm_smart(&lotto, &QueryMsg::Campaign { campaign_id })
+            .unwrap();
+        assert_eq!(campaign.campaign.admin, Addr::unchecked("sponsor"));
+        assert_eq!(campaign.pot.pot_of_rewards, Uint256::from(120u128));
+        assert_eq!(campaign.pot.reward_curve, RewardCurve::EvenSplit {});
+
+        let campaigns: FetchCampaignsResponse = suite.app.wrap()
+            .query_wasm_smart(&lotto, &QueryMsg::FetchCampaigns { start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(campaigns.campaigns.len(), 2);
+        assert_eq!(campaigns.campaigns[0].pot.pot_of_rewards, Uint256::from(300u128));
+
+        // Only the sponsor can cancel their campaign, and that leaves the default one alone
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id }, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id }, &[]).unwrap();
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+    }
+
+    #[test]
+    fn test_claims_follow_the_campaign_policy() {
+        let mut suite = setup();
+
+        let only_ok = create_campaign(&mut suite, vec!["OK".to_string()], vec![], None);
+        let error = claim(&mut suite, "claimant", only_ok).unwrap_err();
+        assert!(error.root_cause().to_string().contains("quote status CONFIGURATION_AND_SW_HARDENING_NEEDED"));
+
+        let other_enclave = create_campaign(&mut suite, vec![], vec![HexBinary::from([0u8; 32])], None);
+        let error = claim(&mut suite, "claimant", other_enclave).unwrap_err();
+        assert!(error.root_cause().to_string().contains("doesn't accept reports from enclave"));
+
+        // The policy is only about the report, the right enclave gets past it
+        let our_enclave = create_campaign(&mut suite, vec![], vec![HexBinary::from_hex(MRENCLAVE).unwrap()], None);
+        let error = claim(&mut suite, "claimant", our_enclave).unwrap_err();
+        assert!(!error.root_cause().to_string().contains("doesn't accept reports from enclave"));
+
+        let later = suite.app.block_info().time.seconds() + 100;
+        let not_started = create_campaign(&mut suite, vec![], vec![], Some(later));
+        let error = claim(&mut suite, "claimant", not_started).unwrap_err();
+        assert!(error.root_cause().to_string().contains("isn't taking claims"));
+    }
+
+    #[test]
+    fn test_campaign_needs_valid_settings() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+
+        let create = |total_reward_units: u128, accepted_mrenclaves: Vec<HexBinary>, start_time: Option<u64>, end_time: Option<u64>| ExecuteMsg::CreateCampaign(CreateCampaignMsg {
+            name: "broken".to_string(),
+            total_reward_units: Uint256::from(total_reward_units),
+            reward_curve: RewardCurve::ShareOfPot {},
+            accepted_mrenclaves,
+            start_time,
+            end_time,
+            ..sponsored_campaign()
+        });
+
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &create(0, vec![], None, None), &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &create(1, vec![HexBinary::from([1u8; 4])], None, None), &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &create(1, vec![], Some(200), Some(100)), &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &create(1, vec![], Some(100), Some(200)), &[]).unwrap();
+
+        let bad_treasury = ExecuteMsg::CreateCampaign(CreateCampaignMsg { treasury: Some("".to_string()), ..sponsored_campaign() });
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &bad_treasury, &[]).unwrap_err();
+
+        let worthless_tier = BonusTier { name: "worthless".to_string(), quote_status: None, requires_up_to_date_cpusvn: false, reward_shares: Decimal256::zero() };
+        let bad_tiers = ExecuteMsg::CreateCampaign(CreateCampaignMsg { bonus_tiers: vec![worthless_tier], ..sponsored_campaign() });
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto, &bad_tiers, &[]).unwrap_err();
+    }
+
+    fn sweep(suite: &mut Suite, sender: &str, campaign_id: u64, limit: Option<u32>) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(sender), lotto, &ExecuteMsg::Sweep { campaign_id, limit }, &[])
+    }
+
+    #[test]
+    fn test_donations_stop_at_the_cutoff() {
+        let mut suite = setup();
+        let now = suite.app.block_info().time.seconds();
+        let campaign_id = create_campaign_from(&mut suite, CreateCampaignMsg { donation_cutoff: Some(now + 50), end_time: Some(now + 100), ..sponsored_campaign() });
+
+        let lotto = suite.lotto.clone();
+        let donate = ExecuteMsg::DonateToPot { campaign_id, expires_at: None };
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &donate, &coins(100, "ujuno")).unwrap();
+
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(50));
+        let error = suite.app.execute_contract(Addr::unchecked("donor1"), lotto, &donate, &coins(100, "ujuno")).unwrap_err();
+        assert!(error.root_cause().to_string().contains("isn't taking donations"));
+    }
+
+    #[test]
+    fn test_sweep_to_the_treasury() {
+        let mut suite = setup();
+        let now = suite.app.block_info().time.seconds();
+        let campaign_id = create_campaign_from(&mut suite, CreateCampaignMsg { end_time: Some(now + 100), treasury: Some("treasury".to_string()), ..sponsored_campaign() });
+
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(300, "ujuno")).unwrap();
+        let send = cw20::Cw20ExecuteMsg::Send {
+            contract: lotto.to_string(),
+            amount: Uint128::new(40),
+            msg: cosmwasm_std::to_json_binary(&ReceiveMsg::DonateToPot { campaign_id, expires_at: None }).unwrap(),
+        };
+        let token = suite.token.clone();
+        suite.app.execute_contract(Addr::unchecked("donor2"), token, &send, &[]).unwrap();
+
+        // Not before the campaign has ended, and only by the admin
+        let error = sweep(&mut suite, "sponsor", campaign_id, None).unwrap_err();
+        assert!(error.root_cause().to_string().contains("hasn't ended yet"));
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(100));
+        sweep(&mut suite, "donor1", campaign_id, None).unwrap_err();
+
+        let response = sweep(&mut suite, "sponsor", campaign_id, None).unwrap();
+        assert!(response.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("finished", "true")));
+        assert_eq!(native_balance(&suite, "treasury"), Uint128::new(300));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("treasury")), Uint128::new(40));
+        assert_eq!(native_balance(&suite, lotto.as_str()), Uint128::zero());
+
+        // Swept once is swept for good
+        sweep(&mut suite, "sponsor", campaign_id, None).unwrap_err();
+    }
+
+    #[test]
+    fn test_cancelled_campaign_is_swept_back_to_the_donors() {
+        let mut suite = setup();
+        let now = suite.app.block_info().time.seconds();
+        let campaign_id = create_campaign_from(&mut suite, CreateCampaignMsg { end_time: Some(now + 100), treasury: Some("treasury".to_string()), ..sponsored_campaign() });
+
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(300, "ujuno")).unwrap();
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(100, "ujuno")).unwrap();
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id }, &[]).unwrap();
+
+        // One donor takes their donation back on their own, the other one forgets to
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto.clone(), &ExecuteMsg::WithdrawDonation { campaign_id }, &[]).unwrap();
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+        // The campaign has a treasury, but what is left is still the donors'
+        sweep(&mut suite, "sponsor", campaign_id, None).unwrap();
+        assert!(native_balance(&suite, "treasury").is_zero());
+        assert_eq!(native_balance(&suite, "donor1"), Uint128::new(1000));
+        assert_eq!(native_balance(&suite, "donor2"), Uint128::new(1000));
+        assert_eq!(native_balance(&suite, lotto.as_str()), Uint128::zero());
+    }
+
+    #[test]
+    fn test_sweep_back_to_the_donors_in_batches() {
+        let mut suite = setup();
+        let now = suite.app.block_info().time.seconds();
+        let campaign_id = create_campaign_from(&mut suite, CreateCampaignMsg { end_time: Some(now + 100), ..sponsored_campaign() });
+
+        let lotto = suite.lotto.clone();
+        let expires_at = now + 1000;
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: Some(expires_at) }, &coins(300, "ujuno")).unwrap();
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(100, "ujuno")).unwrap();
+
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+        // One donor per call
+        let response = sweep(&mut suite, "sponsor", campaign_id, Some(1)).unwrap();
+        assert!(response.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("finished", "false")));
+        // Where the sweep stopped is kept, but the donor's address stays out of the campaign queries
+        let campaign: CampaignResponse = suite.app.wrap().query_wasm_smart(&lotto, &QueryMsg::Campaign { campaign_id }).unwrap();
+        let progress = campaign.pot.sweep.unwrap();
+        assert!(!progress.finished && progress.last_donor.is_none());
+        let campaigns: FetchCampaignsResponse = suite.app.wrap().query_wasm_smart(&lotto, &QueryMsg::FetchCampaigns { start_after: None, limit: None }).unwrap();
+        assert!(campaigns.campaigns.iter().all(|campaign| campaign.pot.sweep.as_ref().map_or(true, |progress| progress.last_donor.is_none())));
+        let response = sweep(&mut suite, "sponsor", campaign_id, Some(1)).unwrap();
+        assert!(response.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("finished", "true")));
+
+        assert_eq!(native_balance(&suite, "donor1"), Uint128::new(1000));
+        assert_eq!(native_balance(&suite, "donor2"), Uint128::new(1000));
+        assert_eq!(native_balance(&suite, lotto.as_str()), Uint128::zero());
+
+        // The swept pot can neither be donated to nor withdrawn from
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(1000));
+        let withdraw = ExecuteMsg::WithdrawDonation { campaign_id };
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto, &withdraw, &[]).unwrap_err();
+        sweep(&mut suite, "sponsor", campaign_id, Some(1)).unwrap_err();
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_list_queries_page_with_a_cursor() {
+        let mut suite = setup();
+        suite.app.send_tokens(Addr::unchecked("donor1"), Addr::unchecked("donor3"), &coins(100, "ujuno")).unwrap();
+
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+        donate_native(&mut suite, "donor2", 10, None).unwrap();
+        donate_native(&mut suite, "donor3", 10, None).unwrap();
+
+        let mut seen = Vec::new();
+        let mut start_after = None;
+        loop {
+            let page: FetchDonorsResponse = suite.app.wrap()
+                .query_wasm_smart(&suite.lotto, &QueryMsg::FetchDonors { campaign_id: 0, start_after, limit: Some(2) })
+                .unwrap();
+            assert!(page.donors.len() <= 2);
+            seen.extend(page.donors.into_iter().map(|(donor, _)| donor));
+
+            start_after = page.next_start_after;
+            if start_after.is_none() {
+                break;
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, vec![Addr::unchecked("donor1"), Addr::unchecked("donor2"), Addr::unchecked("donor3")]);
+
+        // Asking for more than the cap gets a capped page
+        for _ in 0..35 {
+            create_campaign(&mut suite, vec![], vec![], None);
+        }
+        let campaigns: FetchCampaignsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::FetchCampaigns { start_after: None, limit: Some(100) })
+            .unwrap();
+        assert_eq!(campaigns.campaigns.len(), 30);
+        assert_eq!(campaigns.next_start_after, Some(29));
+
+        let campaigns: FetchCampaignsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::FetchCampaigns { start_after: Some(29), limit: Some(100) })
+            .unwrap();
+        assert_eq!(campaigns.campaigns.len(), 6);
+        assert_eq!(campaigns.next_start_after, None);
+
+        let seen_group_ids: FetchSeenGroupIds = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::FetchSeenGuids { campaign_id: 0, start_after: None, limit: None })
+            .unwrap();
+        assert!(seen_group_ids.rewarded.is_empty());
+        assert_eq!(seen_group_ids.next_start_after, None);
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_point_lookups() {
+        let mut suite = setup();
+
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+        donate_native(&mut suite, "donor1", 15, None).unwrap();
+
+        let donor: DonorResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::Donor { campaign_id: 0, address: "donor1".to_string() })
+            .unwrap();
+        assert_eq!(donor.donation_info.unwrap().donation_size, Uint256::from(25u128));
+
+        let stranger: DonorResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::Donor { campaign_id: 0, address: "donor2".to_string() })
+            .unwrap();
+        assert!(stranger.donation_info.is_none());
+
+        let claimant: ClaimantResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::Claimant { campaign_id: 0, address: "donor1".to_string() })
+            .unwrap();
+        assert!(claimant.reward_info.is_none());
+
+        let group_id: GroupIdResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::GroupId { campaign_id: 0, gid: 0xb0c })
+            .unwrap();
+        assert!(group_id.claimant.is_none());
+    }
+
+    #[test]
+    fn test_pot_stats() {
+        let mut suite = setup();
+
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        donate_native(&mut suite, "donor1", 50, None).unwrap();
+        donate_cw20(&mut suite, "donor2", 40).unwrap();
+
+        let stats: PotStatsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::PotStats { campaign_id: 0 })
+            .unwrap();
+        assert_eq!(stats.total_donations.native, Uint256::from(150u128));
+        assert_eq!(stats.pot_of_rewards.cw20, vec![TokenBalance { token: suite.token.clone(), amount: Uint256::from(40u128) }]);
+        assert_eq!(stats.total_paid_out.native, Uint256::zero());
+        assert_eq!(stats.claims, Uint256::zero());
+        assert_eq!(stats.unique_donors, 2);
+        assert_eq!(stats.total_reward_units, Uint256::from(10u128));
+        assert_eq!(stats.remaining_reward_units, None);
+        assert!(stats.policy.accepted_quote_statuses.is_empty());
+        assert_eq!(stats.signing_key_exponent, HexBinary::from_hex("010001").unwrap());
+        assert_eq!(stats.signing_key_modulus, MockIas::default().signing_key().modulus);
+
+        let even_split = create_campaign(&mut suite, vec![], vec![], None);
+        let stats: PotStatsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::PotStats { campaign_id: even_split })
+            .unwrap();
+        assert_eq!(stats.remaining_reward_units, Some(Uint256::from(4u128)));
+    }
+
+    #[test]
+    fn test_verify_report_is_a_dry_run() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+
+        let verify = QueryMsg::VerifyReport {
+            campaign_id: 0,
+            report: serde_json::from_str::<IASReport>(IAS_RESPONSE).unwrap(),
+            to_claim: serde_json::from_str::<ClaimStruct>(CLAIM).unwrap(),
+            claimant: "claimant".to_string(),
+        };
+        let verdict: VerifyReportResponse = suite.app.wrap().query_wasm_smart(&suite.lotto, &verify).unwrap();
+
+        let checks: Vec<(&str, bool)> = verdict.checks.iter().map(|check| (check.check.as_str(), check.passed)).collect();
+        // The suite trusts the mock signer rather than Intel, and the captured claim is for a juno1 address
+        assert_eq!(checks, vec![
+            ("campaign_open", true), ("quote_status", true), ("platform_info_blob", true), ("mrenclave", true),
+            ("payload", true), ("signature", false), ("claimant", false), ("group_id", true),
+        ]);
+        assert!(!verdict.accepted);
+        assert!(verdict.reward.is_none());
+    }
+
+    #[test]
+    fn test_claims_with_reports_from_the_trusted_signer() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+
+        let to_claim = claim_for("claimant");
+        let report = MockIas::default().report(&MockPlatform::claiming(&to_claim));
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &ExecuteMsg::ClaimGEID { campaign_id: 0, report, to_claim: to_claim.clone() }, &[]).unwrap();
+        assert!(!native_balance(&suite, "claimant").is_zero());
+
+        // The same report signed by anyone else isn't trusted
+        let report = MockIas::from_seed(7).report(&MockPlatform::in_group(1, &to_claim));
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto, &ExecuteMsg::ClaimGEID { campaign_id: 0, report, to_claim }, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::SignatureMismatch {}));
+    }
+
+    #[test]
+    fn test_claims_are_attestations_other_contracts_can_gate_on() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        let lotto = CwTemplateContract(suite.lotto.clone());
+        let day = 24 * 60 * 60;
+        let any = AttestationRequirements::default;
+
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant1", any()).unwrap());
+
+        // An accepted claim is an attestation that counts for 90 days from when IAS made the report
+        let attested_at = 1_700_740_025;
+        suite.app.update_block(|block| block.time = Timestamp::from_seconds(attested_at + day));
+        claim_signed(&mut suite, "claimant1", 0, 7).unwrap();
+        let attestation = lotto.attestation(&suite.app.wrap(), "claimant1", any()).unwrap();
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(attestation, IsAttestedResponse {
+            attested: true,
+            attestation: Some(AttestedEnclave {
+                mrenclave: HexBinary::from(MockPlatform::default().mrenclave),
+                quote_status: "OK".to_string(),
+                attested_at,
+                expires_at: attested_at + 90 * day,
+            }),
+        });
+        // The private-queries build only says whether there is one
+        #[cfg(feature = "private-queries")]
+        assert_eq!(attestation, IsAttestedResponse { attested: true, attestation: None });
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant2", any()).unwrap());
+
+        // The report is out in the open now, but replaying it in another campaign attests nobody else
+        let another_campaign = create_campaign(&mut suite, vec![], vec![], None);
+        let replayed = signed_claim(another_campaign, "claimant1", 7);
+        let error = suite.app.execute_contract(Addr::unchecked("claimant2"), suite.lotto.clone(), &replayed, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::ClaimantMismatch {}));
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant2", any()).unwrap());
+
+        // Another enclave attested with a worse status a few days later
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(5 * day));
+        let to_claim = claim_for("claimant1");
+        let platform = MockPlatform {
+            mrenclave: [0xAB; 32],
+            quote_status: "SW_HARDENING_NEEDED".to_string(),
+            timestamp: "2023-11-29T11:47:05.757595".to_string(),
+            ..MockPlatform::in_group(8, &to_claim)
+        };
+        let report = MockIas::default().report(&platform);
+        suite.app.execute_contract(Addr::unchecked("claimant1"), suite.lotto.clone(), &ExecuteMsg::ClaimGEID { campaign_id: 0, report, to_claim }, &[]).unwrap();
+
+        let enclave = |mrenclave: [u8; 32], max_age: Option<u64>| AttestationRequirements { mrenclave: Some(HexBinary::from(mrenclave)), max_age, ..Default::default() };
+        let ok_only = AttestationRequirements { quote_statuses: vec!["OK".to_string()], ..Default::default() };
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(lotto.attestation(&suite.app.wrap(), "claimant1", any()).unwrap().attestation.unwrap().mrenclave, HexBinary::from([0xAB; 32]));
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant1", ok_only.clone()).unwrap());
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant1", enclave([0xAB; 32], Some(day))).unwrap());
+        // "Within the last N days" is up to whoever asks
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant1", enclave(MockPlatform::default().mrenclave, Some(day))).unwrap());
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant1", enclave(MockPlatform::default().mrenclave, Some(7 * day))).unwrap());
+
+        // The first attestation runs out after 90 days, the second one a few days later
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(85 * day));
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant1", ok_only).unwrap());
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant1", any()).unwrap());
+
+        // A revoker can take them all back, nobody else can
+        let revoke = ExecuteMsg::RevokeAttestations { address: "claimant1".to_string() };
+        let error = suite.app.execute_contract(Addr::unchecked("claimant1"), suite.lotto.clone(), &revoke, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::Unauthorized {}));
+        let grant = ExecuteMsg::GrantRole { role: Role::Revoker, address: "security".to_string() };
+        suite.app.execute_contract(Addr::unchecked("creator"), suite.lotto.clone(), &grant, &[]).unwrap();
+        suite.app.execute_contract(Addr::unchecked("security"), suite.lotto.clone(), &revoke, &[]).unwrap();
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant1", any()).unwrap());
+    }
+
+    /// Attests `sender` with a report the suite trusts, made at `timestamp` for a platform in `group_id` that claims for
+    /// `claimant`
+    fn attest_signed(suite: &mut Suite, sender: &str, claimant: &str, group_id: u32, timestamp: &str) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let to_claim = claim_for(claimant);
+        let platform = MockPlatform { timestamp: timestamp.to_string(), ..MockPlatform::in_group(group_id, &to_claim) };
+        let report = MockIas::default().report(&platform);
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(sender), lotto, &ExecuteMsg::Attest { report, to_claim }, &[])
+    }
+
+    #[test]
+    fn test_attesting_without_claiming() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        let lotto = CwTemplateContract(suite.lotto.clone());
+        let day = 24 * 60 * 60;
+        let any = AttestationRequirements::default;
+        let attested_at = 1_700_740_025;
+        suite.app.update_block(|block| block.time = Timestamp::from_seconds(attested_at + day));
+
+        // Another platform in a group that already claimed can still attest, it just isn't paid for it
+        claim_signed(&mut suite, "claimant1", 0, 7).unwrap();
+        let response = attest_signed(&mut suite, "claimant2", "claimant2", 7, "2023-11-23T11:47:05.757595").unwrap();
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant2", any()).unwrap());
+        assert!(native_balance(&suite, "claimant2").is_zero());
+        let attributes = event_attributes(&response, "wasm");
+        assert!(attributes.contains(&("action", "attest")));
+        #[cfg(not(feature = "private-queries"))]
+        assert!(attributes.contains(&("expires_at", (attested_at + 90 * day).to_string().as_str())));
+        // The private-queries build doesn't tell which enclave the address runs
+        #[cfg(feature = "private-queries")]
+        assert_eq!(attributes.len(), 2);
+
+        // Somebody else's report attests nobody
+        let error = attest_signed(&mut suite, "claimant3", "claimant1", 7, "2023-11-23T11:47:05.757595").unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::ClaimantMismatch {}));
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant3", any()).unwrap());
+
+        // Paused claims and closed campaigns don't stop anyone from attesting
+        let pause = ExecuteMsg::Pause { claims: true, donations: true, reason: None, until: None };
+        let grant = ExecuteMsg::GrantRole { role: Role::Pauser, address: "guardian".to_string() };
+        suite.app.execute_contract(Addr::unchecked("creator"), suite.lotto.clone(), &grant, &[]).unwrap();
+        suite.app.execute_contract(Addr::unchecked("guardian"), suite.lotto.clone(), &pause, &[]).unwrap();
+        suite.app.execute_contract(Addr::unchecked("creator"), suite.lotto.clone(), &ExecuteMsg::CancelCampaign { campaign_id: 0 }, &[]).unwrap();
+        attest_signed(&mut suite, "claimant3", "claimant3", 8, "2023-11-23T11:47:05.757595").unwrap();
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant3", any()).unwrap());
+
+        // An attestation that ran out is renewed with a newer report
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(90 * day));
+        assert!(!lotto.is_attested(&suite.app.wrap(), "claimant2", any()).unwrap());
+        attest_signed(&mut suite, "claimant2", "claimant2", 7, "2024-02-22T11:47:05.757595").unwrap();
+        assert!(lotto.is_attested(&suite.app.wrap(), "claimant2", any()).unwrap());
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(lotto.attestation(&suite.app.wrap(), "claimant2", any()).unwrap().attestation.unwrap().expires_at, attested_at + 181 * day);
+    }
+
+    #[test]
+    // Donors are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_top_donors() {
+        let mut suite = setup();
+
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+        donate_native(&mut suite, "donor2", 30, None).unwrap();
+
+        let top: FetchDonorsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::TopDonors { campaign_id: 0, start_after: None, limit: Some(1) })
+            .unwrap();
+        assert_eq!(top.donors[0].0, Addr::unchecked("donor2"));
+        assert_eq!(top.next_start_after, Some("donor2".to_string()));
+
+        let rest: FetchDonorsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::TopDonors { campaign_id: 0, start_after: top.next_start_after, limit: Some(1) })
+            .unwrap();
+        assert_eq!(rest.donors[0].0, Addr::unchecked("donor1"));
+    }
+
+    fn roles(suite: &Suite) -> RolesResponse {
+        suite.app.wrap().query_wasm_smart(&suite.lotto, &QueryMsg::Roles {}).unwrap()
+    }
+
+    #[test]
+    fn test_ownership_is_handed_over_in_two_steps() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        assert_eq!(roles(&suite).owner, Addr::unchecked("creator"));
+
+        let transfer = ExecuteMsg::TransferOwnership { new_owner: "successor".to_string() };
+        let error = suite.app.execute_contract(Addr::unchecked("successor"), lotto.clone(), &transfer, &[]).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &transfer, &[]).unwrap();
+
+        // Nothing changes until the new owner accepts, and nobody else can accept for them
+        assert_eq!(roles(&suite).owner, Addr::unchecked("creator"));
+        assert_eq!(roles(&suite).pending_owner, Some(Addr::unchecked("successor")));
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::AcceptOwnership {}, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("successor"), lotto.clone(), &ExecuteMsg::AcceptOwnership {}, &[]).unwrap();
+
+        let roles = roles(&suite);
+        assert_eq!(roles.owner, Addr::unchecked("successor"));
+        assert_eq!(roles.pending_owner, None);
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto, &transfer, &[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_roles_gate_privileged_messages() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        let campaign_id = create_campaign(&mut suite, vec![], vec![], None);
+
+        let grant = ExecuteMsg::GrantRole { role: Role::PolicyAdmin, address: "ops".to_string() };
+        suite.app.execute_contract(Addr::unchecked("ops"), lotto.clone(), &grant, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &grant, &[]).unwrap();
+        let revoker = ExecuteMsg::GrantRole { role: Role::Revoker, address: "security".to_string() };
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &revoker, &[]).unwrap();
+
+        let roles = roles(&suite);
+        assert_eq!(roles.policy_admins, vec![Addr::unchecked("ops")]);
+        assert_eq!(roles.revokers, vec![Addr::unchecked("security")]);
+        assert!(roles.pausers.is_empty());
+
+        // A policy admin can change any campaign's policy, a revoker can't
+        let policy: ClaimPolicy = serde_json::from_str(r#"{"accepted_quote_statuses":["OK"],"accepted_mrenclaves":[]}"#).unwrap();
+        let update = ExecuteMsg::UpdatePolicy { campaign_id, policy: policy.clone() };
+        let error = suite.app.execute_contract(Addr::unchecked("security"), lotto.clone(), &update, &[]).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+        suite.app.execute_contract(Addr::unchecked("ops"), lotto.clone(), &update, &[]).unwrap();
+        let campaign: CampaignResponse = suite.app.wrap().query_wasm_smart(&lotto, &QueryMsg::Campaign { campaign_id }).unwrap();
+        assert_eq!(campaign.campaign.policy, policy);
+
+        // A revoker can refuse a broken enclave, the policy admin can't
+        let revoke = ExecuteMsg::RevokeMrenclave { campaign_id, mrenclave: HexBinary::from_hex(MRENCLAVE).unwrap() };
+        suite.app.execute_contract(Addr::unchecked("ops"), lotto.clone(), &revoke, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("security"), lotto.clone(), &revoke, &[]).unwrap();
+        let update = ExecuteMsg::UpdatePolicy { campaign_id, policy: ClaimPolicy::default() };
+        suite.app.execute_contract(Addr::unchecked("sponsor"), lotto.clone(), &update, &[]).unwrap();
+        let campaign: CampaignResponse = suite.app.wrap().query_wasm_smart(&lotto, &QueryMsg::Campaign { campaign_id }).unwrap();
+        assert!(campaign.campaign.policy.revoked_mrenclaves.is_empty());
+
+        // Revoking the role takes the permission away with it
+        let revoke_role = ExecuteMsg::RevokeRole { role: Role::PolicyAdmin, address: "ops".to_string() };
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &revoke_role, &[]).unwrap();
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &revoke_role, &[]).unwrap_err();
+        suite.app.execute_contract(Addr::unchecked("ops"), lotto.clone(), &update, &[]).unwrap_err();
+
+        // Holding a role doesn't make anyone the admin of someone else's campaign
+        let cancel = ExecuteMsg::CancelCampaign { campaign_id };
+        let error = suite.app.execute_contract(Addr::unchecked("security"), lotto, &cancel, &[]).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+    }
+
+    fn pause_status(suite: &Suite) -> PauseResponse {
+        suite.app.wrap().query_wasm_smart(&suite.lotto, &QueryMsg::PauseStatus {}).unwrap()
+    }
+
+    #[test]
+    fn test_pause_stops_claims_and_donations() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        let grant = ExecuteMsg::GrantRole { role: Role::Pauser, address: "guardian".to_string() };
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto.clone(), &grant, &[]).unwrap();
+
+        let pause = ExecuteMsg::Pause { claims: true, donations: false, reason: Some("verification bug".to_string()), until: None };
+        let error = suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &pause, &[]).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized {})));
+        let response = suite.app.execute_contract(Addr::unchecked("guardian"), lotto.clone(), &pause, &[]).unwrap();
+        let event = response.events.iter().find(|event| event.ty == "wasm-pause").unwrap();
+        assert!(event.attributes.iter().any(|attribute| attribute.key == "reason" && attribute.value == "verification bug"));
+
+        let status = pause_status(&suite);
+        assert!(status.claims_paused);
+        assert!(!status.donations_paused);
+        assert_eq!(status.pause.unwrap().paused_by, Addr::unchecked("guardian"));
+
+        // Claims are refused before the report is even looked at, donations still go through
+        let error = claim(&mut suite, "claimant", 0).unwrap_err();
+        assert!(error.root_cause().to_string().contains("Claims are paused: verification bug"));
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+
+        let pause_donations = ExecuteMsg::Pause { claims: false, donations: true, reason: None, until: None };
+        suite.app.execute_contract(Addr::unchecked("guardian"), lotto.clone(), &pause_donations, &[]).unwrap();
+        donate_native(&mut suite, "donor1", 10, None).unwrap_err();
+        donate_cw20(&mut suite, "donor1", 10).unwrap_err();
+        let error = claim(&mut suite, "claimant", 0).unwrap_err();
+        assert!(!error.root_cause().to_string().contains("paused"));
+
+        let response = suite.app.execute_contract(Addr::unchecked("guardian"), lotto.clone(), &ExecuteMsg::Unpause {}, &[]).unwrap();
+        assert!(response.events.iter().any(|event| event.ty == "wasm-unpause"));
+        assert_eq!(pause_status(&suite), PauseResponse { claims_paused: false, donations_paused: false, pause: None });
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+        suite.app.execute_contract(Addr::unchecked("guardian"), lotto, &ExecuteMsg::Unpause {}, &[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_pause_lifts_on_its_own() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        let until = suite.app.block_info().time.seconds() + 100;
+
+        // The owner holds the pauser role without being granted it
+        let pause = ExecuteMsg::Pause { claims: true, donations: true, reason: None, until: Some(until) };
+        suite.app.execute_contract(Addr::unchecked("creator"), lotto, &pause, &[]).unwrap();
+        donate_native(&mut suite, "donor1", 10, None).unwrap_err();
+
+        suite.app.update_block(|block| block.time = block.time.plus_seconds(100));
+        let status = pause_status(&suite);
+        assert!(!status.claims_paused && !status.donations_paused);
+        assert_eq!(status.pause.unwrap().until, Some(until));
+        donate_native(&mut suite, "donor1", 10, None).unwrap();
+    }
+
+    #[test]
+    fn test_donations_need_funds() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        let donate = ExecuteMsg::DonateToPot { campaign_id: 0, expires_at: None };
+
+        let error = suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &donate, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::NoFunds {}));
+        let error = donate_cw20(&mut suite, "donor1", 0).unwrap_err();
+        assert_eq!(error.root_cause().downcast_ref::<ContractError>(), Some(&ContractError::NoFunds {}));
+
+        // The juno doesn't have to come first
+        suite.app.sudo(cw_multi_test::SudoMsg::Bank(cw_multi_test::BankSudo::Mint { to_address: "donor1".to_string(), amount: coins(10, "uatom") })).unwrap();
+        let funds = vec![cosmwasm_std::Coin::new(5, "uatom"), cosmwasm_std::Coin::new(20, "ujuno")];
+        let response = suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &donate, &funds).unwrap();
+        assert!(response.events.iter().flat_map(|event| &event.attributes).any(|attribute| attribute.key == "donation" && attribute.value == "20"));
+
+        let error = suite.app.execute_contract(Addr::unchecked("donor1"), lotto, &donate, &coins(5, "uatom")).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::NoFunds {}));
+    }
+
+    #[test]
+    fn test_malformed_claims_are_refused_with_typed_errors() {
+        let mut suite = setup();
+        let lotto = suite.lotto.clone();
+        let claim_with = |report: IASReport| ExecuteMsg::ClaimGEID { campaign_id: 0, report, to_claim: serde_json::from_str::<ClaimStruct>(CLAIM).unwrap() };
+
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.isv_enclave_quote_body = "%%%".to_string();
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &claim_with(report), &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::InvalidBase64 { field: "isvEnclaveQuoteBody".to_string() }));
+
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.platform_info_blob = "15".to_string();
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &claim_with(report), &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::InvalidPlatformInfoBlob {}));
+
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let other_claim = ExecuteMsg::ClaimGEID { campaign_id: 0, report, to_claim: serde_json::from_str(r#"{"address":[],"message":""}"#).unwrap() };
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &other_claim, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::PayloadMismatch {}));
+
+        let error = claim(&mut suite, "claimant", 0).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::SignatureMismatch {}));
+    }
+
+    fn event_attributes<'a>(response: &'a cw_multi_test::AppResponse, ty: &str) -> Vec<(&'a str, &'a str)> {
+        let event = response.events.iter().find(|event| event.ty == ty).unwrap();
+        event.attributes.iter().map(|attribute| (attribute.key.as_str(), attribute.value.as_str())).collect()
+    }
+
+    #[test]
+    fn test_donations_emit_events() {
+        let mut suite = setup();
+
+        let response = donate_native(&mut suite, "donor1", 300, None).unwrap();
+        let attributes = event_attributes(&response, "wasm-donate");
+        #[cfg(not(feature = "private-queries"))]
+        assert_eq!(attributes[1..], [
+            ("action", "donate"), ("campaign_id", "0"), ("donor", "donor1"), ("amount", "300ujuno"), ("expires_at", "never"),
+            ("pot_balance", "300ujuno"),
+        ]);
+        // Donors are private on the private-queries build
+        #[cfg(feature = "private-queries")]
+        assert_eq!(attributes[1..], [
+            ("action", "donate"), ("campaign_id", "0"), ("amount", "300ujuno"), ("expires_at", "never"), ("pot_balance", "300ujuno"),
+        ]);
+
+        let response = donate_cw20(&mut suite, "donor2", 50).unwrap();
+        let token_amount = format!("50cw20:{}", suite.token);
+        let pot_balance = format!("300ujuno,{}", token_amount);
+        let attributes = event_attributes(&response, "wasm-donate");
+        #[cfg(not(feature = "private-queries"))]
+        assert!(attributes.contains(&("donor", "donor2")));
+        assert!(attributes.contains(&("amount", token_amount.as_str())));
+        assert!(attributes.contains(&("pot_balance", pot_balance.as_str())));
+    }
+
+    // End to end: reports signed by the mock IAS that the suite trusts, so claims go all the way through
+
+    /// The claim an enclave makes for `claimant`, with the canonical address the suite's mock API gives them
+    fn claim_for(claimant: &str) -> ClaimStruct {
+        ClaimStruct::for_account(MockApi::default().addr_canonicalize(claimant).unwrap().as_slice(), "claiming".to_string())
+    }
+
+    /// A claim by `claimant` from a platform in `group_id`, in a report the suite trusts
+    fn signed_claim(campaign_id: u64, claimant: &str, group_id: u32) -> ExecuteMsg {
+        let to_claim = claim_for(claimant);
+        let report = MockIas::default().report(&MockPlatform::in_group(group_id, &to_claim));
+        ExecuteMsg::ClaimGEID { campaign_id, report, to_claim }
+    }
+
+    fn claim_signed(suite: &mut Suite, claimant: &str, campaign_id: u64, group_id: u32) -> anyhow::Result<cw_multi_test::AppResponse> {
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked(claimant), lotto, &signed_claim(campaign_id, claimant, group_id), &[])
+    }
+
+    fn pot_stats(suite: &Suite, campaign_id: u64) -> PotStatsResponse {
+        suite.app.wrap().query_wasm_smart(&suite.lotto, &QueryMsg::PotStats { campaign_id }).unwrap()
+    }
+
+    fn group_id_claimant(suite: &Suite, campaign_id: u64, gid: u32) -> Option<Addr> {
+        let response: GroupIdResponse = suite.app.wrap().query_wasm_smart(&suite.lotto, &QueryMsg::GroupId { campaign_id, gid }).unwrap();
+        response.claimant
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_end_to_end_claims_pay_out_of_the_pot() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 60, None).unwrap();
+        donate_native(&mut suite, "donor2", 40, None).unwrap();
+        donate_cw20(&mut suite, "donor1", 50).unwrap();
+
+        // Every claim takes a tenth of what is left, rounded down
+        let first = claim_signed(&mut suite, "claimant1", 0, 1).unwrap();
+        claim_signed(&mut suite, "claimant2", 0, 2).unwrap();
+        claim_signed(&mut suite, "claimant3", 0, 3).unwrap();
+
+        assert_eq!(native_balance(&suite, "claimant1"), Uint128::new(10));
+        assert_eq!(native_balance(&suite, "claimant2"), Uint128::new(9));
+        assert_eq!(native_balance(&suite, "claimant3"), Uint128::new(8));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("claimant1")), Uint128::new(5));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("claimant2")), Uint128::new(4));
+        assert_eq!(token_balance(&suite, &Addr::unchecked("claimant3")), Uint128::new(4));
+        assert_eq!(native_balance(&suite, suite.lotto.as_str()), Uint128::new(73));
+        assert_eq!(token_balance(&suite, &suite.lotto), Uint128::new(37));
+
+        let token = format!("cw20:{}", suite.token);
+        let amount = format!("10ujuno,5{}", token);
+        let pot_balance = format!("90ujuno,45{}", token);
+        assert_eq!(event_attributes(&first, "wasm-claim")[1..], [
+            ("action", "claim"), ("campaign_id", "0"), ("claimant", "claimant1"), ("gid", "00000001"),
+            ("epid_pseudonym_hash", event_attributes(&first, "wasm-claim")[5].1), ("quote_status", "OK"), ("report_id", "1"),
+            ("amount", amount.as_str()), ("reward_shares", "1"), ("bonus_tier", "none"), ("pot_balance", pot_balance.as_str()),
+        ]);
+
+        let stats = pot_stats(&suite, 0);
+        assert_eq!(stats.claims, Uint256::from(3u128));
+        assert_eq!(stats.total_paid_out.native, Uint256::from(27u128));
+        assert_eq!(stats.pot_of_rewards.native, Uint256::from(73u128));
+        assert_eq!(stats.total_donations.native, Uint256::from(100u128));
+
+        let claimant: ClaimantResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::Claimant { campaign_id: 0, address: "claimant2".to_string() })
+            .unwrap();
+        assert_eq!(claimant.reward_info.unwrap().received.native, Uint256::from(9u128));
+        assert_eq!(group_id_claimant(&suite, 0, 3), Some(Addr::unchecked("claimant3")));
+
+        let claims: ClaimsResponse = suite.app.wrap()
+            .query_wasm_smart(&suite.lotto, &QueryMsg::ClaimsByAddress { address: "claimant1".to_string(), start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(claims.claims.len(), 1);
+        assert_eq!(claims.claims[0].1.group_id, 1);
+        assert_eq!(claims.claims[0].1.quote_status, "OK");
+    }
+
+    #[test]
+    fn test_end_to_end_group_ids_claim_once() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        claim_signed(&mut suite, "claimant1", 0, 7).unwrap();
+
+        // Another report from the same group, even with another claimant, is turned away without paying anything
+        let error = claim_signed(&mut suite, "claimant2", 0, 7).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::GroupAlreadyClaimed { gid: 7, claimant: Addr::unchecked("claimant1") }));
+        let error = claim_signed(&mut suite, "claimant1", 0, 7).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ContractError>(), Some(ContractError::GroupAlreadyClaimed { gid: 7, .. })));
+        assert!(native_balance(&suite, "claimant2").is_zero());
+        assert_eq!(pot_stats(&suite, 0).claims, Uint256::one());
+
+        // The same claimant can claim again from another group
+        claim_signed(&mut suite, "claimant1", 0, 8).unwrap();
+        assert_eq!(native_balance(&suite, "claimant1"), Uint128::new(19));
+
+        // Campaigns keep their own record of the group ids they have seen
+        let campaign_id = create_campaign(&mut suite, vec![], vec![], None);
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto, &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(40, "ujuno")).unwrap();
+        claim_signed(&mut suite, "claimant2", campaign_id, 7).unwrap();
+        assert_eq!(native_balance(&suite, "claimant2"), Uint128::new(10));
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_end_to_end_invalid_claims_leave_no_trace() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        let only_ok = create_campaign(&mut suite, vec!["OK".to_string()], vec![], None);
+        let lotto = suite.lotto.clone();
+
+        let refused_status = |campaign_id: u64| {
+            let to_claim = claim_for("claimant");
+            let platform = MockPlatform { quote_status: "GROUP_OUT_OF_DATE".to_string(), ..MockPlatform::in_group(9, &to_claim) };
+            ExecuteMsg::ClaimGEID { campaign_id, report: MockIas::default().report(&platform), to_claim }
+        };
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &refused_status(only_ok), &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::QuoteStatusNotAccepted { status: "GROUP_OUT_OF_DATE".to_string() }));
+
+        // A report that was changed after it was signed
+        let mut tampered = signed_claim(0, "claimant", 9);
+        if let ExecuteMsg::ClaimGEID { report, .. } = &mut tampered {
+            report.report.advisory_ids.push("INTEL-SA-00000".to_string());
+        }
+        let error = suite.app.execute_contract(Addr::unchecked("claimant"), lotto.clone(), &tampered, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::SignatureMismatch {}));
+
+        // Somebody else's report as it is
+        let error = suite.app.execute_contract(Addr::unchecked("thief"), lotto.clone(), &signed_claim(0, "claimant", 9), &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::ClaimantMismatch {}));
+
+        // A report that carries somebody else's claim
+        let mut stolen = signed_claim(0, "claimant", 9);
+        if let ExecuteMsg::ClaimGEID { to_claim, .. } = &mut stolen {
+            *to_claim = claim_for("thief");
+        }
+        let error = suite.app.execute_contract(Addr::unchecked("thief"), lotto, &stolen, &[]).unwrap_err();
+        assert_eq!(error.downcast_ref::<ContractError>(), Some(&ContractError::PayloadMismatch {}));
+
+        // None of them used up the group id or touched the pot
+        assert!(group_id_claimant(&suite, 0, 9).is_none());
+        assert_eq!(pot_stats(&suite, 0).claims, Uint256::zero());
+        assert_eq!(native_balance(&suite, suite.lotto.as_str()), Uint128::new(100));
+        claim_signed(&mut suite, "claimant", 0, 9).unwrap();
+        assert_eq!(native_balance(&suite, "claimant"), Uint128::new(10));
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_end_to_end_even_split_runs_dry() {
+        let mut suite = setup();
+        let campaign_id = create_campaign(&mut suite, vec![], vec![], None);
+        let lotto = suite.lotto.clone();
+        suite.app.execute_contract(Addr::unchecked("donor1"), lotto.clone(), &ExecuteMsg::DonateToPot { campaign_id, expires_at: None }, &coins(100, "ujuno")).unwrap();
+
+        // Four reward units split the pot evenly between the first four claims
+        for gid in 1..=4 {
+            claim_signed(&mut suite, &format!("claimant{}", gid), campaign_id, gid).unwrap();
+            assert_eq!(native_balance(&suite, &format!("claimant{}", gid)), Uint128::new(25));
+        }
+        let stats = pot_stats(&suite, campaign_id);
+        assert_eq!(stats.remaining_reward_units, Some(Uint256::zero()));
+        assert!(stats.pot_of_rewards.native.is_zero());
+        assert!(native_balance(&suite, lotto.as_str()).is_zero());
+
+        // The dry run already knows the next claim won't be paid
+        let verify = QueryMsg::VerifyReport {
+            campaign_id,
+            report: match signed_claim(campaign_id, "claimant5", 5) { ExecuteMsg::ClaimGEID { report, .. } => report, _ => unreachable!() },
+            to_claim: claim_for("claimant5"),
+            claimant: "claimant5".to_string(),
+        };
+        let verdict: VerifyReportResponse = suite.app.wrap().query_wasm_smart(&lotto, &verify).unwrap();
+        assert!(!verdict.accepted);
+        assert_eq!(verdict.checks.last().map(|check| (check.check.as_str(), check.passed)), Some(("reward", false)));
+
+        let error = claim_signed(&mut suite, "claimant5", campaign_id, 5).unwrap_err();
+        assert!(error.root_cause().to_string().contains("Every reward unit of this campaign has been handed out"));
+        assert!(group_id_claimant(&suite, campaign_id, 5).is_none());
+    }
+
+    #[test]
+    // Claimants are private on the private-queries build
+    #[cfg(not(feature = "private-queries"))]
+    fn test_end_to_end_share_of_pot_dwindles() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 25, None).unwrap();
+
+        claim_signed(&mut suite, "claimant1", 0, 1).unwrap();
+        claim_signed(&mut suite, "claimant2", 0, 2).unwrap();
+        assert_eq!(native_balance(&suite, "claimant1"), Uint128::new(2));
+        assert_eq!(native_balance(&suite, "claimant2"), Uint128::new(2));
+
+        // Once a tenth of the pot rounds down to nothing, claims still go through but pay nothing
+        let mut pot = 21;
+        let mut gid = 3;
+        while pot >= 10 {
+            claim_signed(&mut suite, "claimant3", 0, gid).unwrap();
+            pot -= pot / 10;
+            gid += 1;
+        }
+        let response = claim_signed(&mut suite, "claimant4", 0, gid).unwrap();
+        assert!(native_balance(&suite, "claimant4").is_zero());
+        assert!(event_attributes(&response, "wasm-claim").contains(&("amount", "0ujuno")));
+        assert_eq!(group_id_claimant(&suite, 0, gid), Some(Addr::unchecked("claimant4")));
+        assert_eq!(native_balance(&suite, suite.lotto.as_str()), Uint128::new(pot));
+    }
+
+    #[test]
+    #[cfg(feature = "private-queries")]
+    fn test_private_queries_claims_are_only_shown_to_the_claimant() {
+        let mut suite = setup();
+        donate_native(&mut suite, "donor1", 100, None).unwrap();
+        let response = claim_signed(&mut suite, "claimant1", 0, 7).unwrap();
+
+        // Nothing public tells who claimed or with which group id
+        assert!(response.events.iter().flat_map(|event| &event.attributes)
+            .filter(|attribute| attribute.key != "_contract_address" && attribute.key != "recipient")
+            .all(|attribute| attribute.value != "claimant1" && attribute.value != "00000007"));
+        let private = [
+            QueryMsg::FetchClaimants { campaign_id: 0, start_after: None, limit: None },
+            QueryMsg::FetchSeenGuids { campaign_id: 0, start_after: None, limit: None },
+            QueryMsg::Claimant { campaign_id: 0, address: "claimant1".to_string() },
+            QueryMsg::GroupId { campaign_id: 0, gid: 7 },
+            QueryMsg::ClaimsByAddress { address: "claimant1".to_string(), start_after: None, limit: None },
+            QueryMsg::ClaimsInRange { start_time: 0, end_time: u64::MAX, start_after: None, limit: None },
+        ];
+        for query in private {
+            let error = suite.app.wrap().query_wasm_smart::<serde_json::Value>(&suite.lotto, &query).unwrap_err();
+            assert!(error.to_string().contains("Claimants are private on this contract"), "{}", error);
+        }
+        let error = claim_signed(&mut suite, "claimant2", 0, 7).unwrap_err();
+        assert_eq!(error.root_cause().to_string(), "Group ID : 00000007 has already been claimed");
+
+        // The claimant sees their own claims with their viewing key, nobody else does
+        let my_claims = |suite: &Suite, key: &str| suite.app.wrap().query_wasm_smart::<ClaimsResponse>(&suite.lotto, &QueryMsg::MyClaims {
+            address: "claimant1".to_string(),
+            key: key.to_string(),
+            start_after: None,
+            limit: None,
+        });
+        assert!(my_claims(&suite, "").unwrap_err().to_string().contains("Wrong viewing key for this address or viewing key not set"));
+
+        let lotto = suite.lotto.clone();
+        let set_key = ExecuteMsg::SetViewingKey { key: "hunter2".to_string(), padding: None };
+        let response = suite.app.execute_contract(Addr::unchecked("claimant1"), lotto, &set_key, &[]).unwrap();
+        assert!(response.events.iter().flat_map(|event| &event.attributes).all(|attribute| attribute.value != "hunter2"));
+
+        let claims = my_claims(&suite, "hunter2").unwrap().claims;
+        assert_eq!(claims.len(), 1);
+        assert_eq!((claims[0].1.group_id, claims[0].1.claimant.as_str()), (7, "claimant1"));
+        assert!(my_claims(&suite, "hunter3").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "private-queries")]
+    fn test_private_queries_donors_and_claimants_see_their_own_with_a_permit_or_a_viewing_key() {
+        use crate::msg::QueryWithPermit;
+        use crate::permits::PermitPermission;
+        use crate::permits::mock_wallet::MockWallet;
+
+        let mut suite = setup();
+        let wallet = MockWallet::from_seed(7);
+        let signer = wallet.address();
+        suite.app.send_tokens(Addr::unchecked("donor1"), Addr::unchecked(&signer), &coins(100, "ujuno")).unwrap();
+        donate_native(&mut suite, &signer, 60, None).unwrap();
+        donate_native(&mut suite, "donor2", 40, None).unwrap();
+        claim_signed(&mut suite, &signer, 0, 7).unwrap();
+
+        // Lists and lookups of donors are refused, everyone still sees the totals
+        let private = [
+            QueryMsg::FetchDonors { campaign_id: 0, start_after: None, limit: None },
+            QueryMsg::TopDonors { campaign_id: 0, start_after: None, limit: None },
+            QueryMsg::Donor { campaign_id: 0, address: "donor2".to_string() },
+            QueryMsg::FetchDonations { campaign_id: 0, donor: "donor2".to_string() },
+        ];
+        for query in private {
+            let error = suite.app.wrap().query_wasm_smart::<serde_json::Value>(&suite.lotto, &query).unwrap_err();
+            assert!(error.to_string().contains("Donors are private on this contract"), "{}", error);
+        }
+        let stats = pot_stats(&suite, 0);
+        assert_eq!((stats.unique_donors, stats.claims, stats.total_donations.native), (2, Uint256::one(), Uint256::from(100u128)));
+
+        // The signer of a permit sees what it allows of their own
+        let chain_id = suite.app.block_info().chain_id;
+        let with_permit = |suite: &Suite, permissions: Vec<PermitPermission>, query: QueryWithPermit| {
+            let permit = wallet.permit("lotto", suite.lotto.as_str(), &chain_id, permissions);
+            suite.app.wrap().query_wasm_smart::<serde_json::Value>(&suite.lotto, &QueryMsg::WithPermit { permit, query })
+        };
+        let donations: FetchDonationsResponse = serde_json::from_value(
+            with_permit(&suite, vec![PermitPermission::Donations], QueryWithPermit::MyDonations { campaign_id: 0 }).unwrap()).unwrap();
+        assert_eq!(donations.donations.len(), 1);
+        assert_eq!(donations.donations[0].entry.amount, Uint256::from(60u128));
+        assert!(with_permit(&suite, vec![PermitPermission::Donations], QueryWithPermit::MyReward { campaign_id: 0 }).is_err());
+        let reward: ClaimantResponse = serde_json::from_value(
+            with_permit(&suite, vec![PermitPermission::Owner], QueryWithPermit::MyReward { campaign_id: 0 }).unwrap()).unwrap();
+        assert!(reward.reward_info.is_some());
+        let claims: ClaimsResponse = serde_json::from_value(
+            with_permit(&suite, vec![PermitPermission::Claims], QueryWithPermit::MyClaims { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(claims.claims.len(), 1);
+
+        // Until they revoke it
+        let lotto = suite.lotto.clone();
+        let revoke = ExecuteMsg::RevokePermit { permit_name: "lotto".to_string(), padding: None };
+        suite.app.execute_contract(Addr::unchecked(&signer), lotto.clone(), &revoke, &[]).unwrap();
+        let error = with_permit(&suite, vec![PermitPermission::Owner], QueryWithPermit::MyReward { campaign_id: 0 }).unwrap_err();
+        assert!(error.to_string().contains("The permit lotto was revoked"), "{}", error);
+
+        // A viewing key works the same way
+        let set_key = ExecuteMsg::SetViewingKey { key: "hunter2".to_string(), padding: None };
+        suite.app.execute_contract(Addr::unchecked("donor2"), lotto, &set_key, &[]).unwrap();
+        let my_donations = |key: &str| suite.app.wrap().query_wasm_smart::<FetchDonationsResponse>(&suite.lotto, &QueryMsg::MyDonations {
+            address: "donor2".to_string(),
+            key: key.to_string(),
+            campaign_id: 0,
+        });
+        assert_eq!(my_donations("hunter2").unwrap().donations[0].entry.amount, Uint256::from(40u128));
+        assert!(my_donations("hunter3").is_err());
+        let reward: ClaimantResponse = suite.app.wrap().query_wasm_smart(&suite.lotto, &QueryMsg::MyReward {
+            address: "donor2".to_string(),
+            key: "hunter2".to_string(),
+            campaign_id: 0,
+        }).unwrap();
+        assert_eq!(reward.reward_info, None);
+    }
+}
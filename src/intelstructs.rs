@@ -1,13 +1,24 @@
+use bech32::ToBase32;
 use cosmwasm_std::Binary;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use hex_literal::hex;
 use num_bigint::BigUint;
 
+use crate::ContractError;
+
+/// The 3072 bit key of the Intel SGX Attestation Report Signing CA, the root of the chain IAS sends along with its
+/// reports. It signs the report signing certificate, never a report.
 pub static INTEL_ROOT_MODULUS: &[u8] = &hex!("9F3C647EB5773CBB512D2732C0D7415EBB55A0FA9EDE2E649199E6821DB910D53177370977466A6A5E4786CCD2DDEBD4149D6A2F6325529DD10CC98737B0779C1A07E29C47A1AE004948476C489F45A5A15D7AC8ECC6ACC645ADB43D87679DF59C093BC5A2E9696C5478541B979E754B573914BE55D32FF4C09DDF27219934CD990527B3F92ED78FBF29246ABECB71240EF39C2D7107B447545A7FFB10EB060A68A98580219E36910952683892D6A5E2A80803193E407531404E36B315623799AA825074409754A2DFE8F5AFD5FE631E1FC2AF3808906F28A790D9DD9FE060939B125790C5805D037DF56A99531B96DE69DE33ED226CC1207D1042B5C9AB7F404FC711C0FE4769FB9578B1DC0EC469EA1A25E0FF9914886EF2699B235BB4847DD6FF40B606E6170793C2FB98B314587F9CFD257362DFEAB10B3BD2D97673A1A4BD44C453AAF47FC1F2D3D0F384F74A06F89C089F0DA6CDB7FCEEE8C9821A8E54F25C0416D18C46839A5F8012FBDD3DC74D256279ADC2C0D55AFF6F0622425D1B");
 
 pub static INTEL_ROOT_EXPONENT: &[u8] = &hex!("010001");
 
+/// The 2048 bit key of the Intel SGX Attestation Report Signing certificate, which IAS signs every report with. The
+/// certificate was issued by the root above and is valid until 2026-11-20.
+pub static IAS_REPORT_SIGNING_MODULUS: &[u8] = &hex!("A97A2DE0E66EA6147C9EE745AC0162686C7192099AFC4B3F040FAD6DE093511D74E802F510D716038157DCAF84F4104BD3FED7E6B8F99C8817FD1FF5B9B864296C3D81FA8F1B729E02D21D72FFEE4CED725EFE74BEA68FBC4D4244286FCDD4BF64406A439A15BCB4CF67754489C423972B4A80DF5C2E7C5BC2DBAF2D42BB7B244F7C95BF92C75D3B33FC5410678A89589D1083DA3ACC459F2704CD99598C275E7C1878E00757E5BDB4E840226C11C0A17FF79C80B15C1DDB5AF21CC2417061FBD2A2DA819ED3B72B7EFAA3BFEBE2805C9B8AC19AA346512D484CFC81941E15F55881CC127E8F7AA12300CD5AFB5742FA1D20CB467A5BEB1C666CF76A368978B5");
+
+pub static IAS_REPORT_SIGNING_EXPONENT: &[u8] = &hex!("010001");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ReportBody {
     pub id: String,
@@ -27,6 +38,42 @@ pub struct ReportBody {
     pub isv_enclave_quote_body: String
 }
 
+impl ReportBody {
+    /// When IAS made the report, in unix seconds. IAS timestamps are in UTC without a zone and with microseconds,
+    /// e.g. `2023-11-23T11:47:05.757595`. The fraction of a second is dropped.
+    pub fn timestamp_seconds(&self) -> Result<u64, ContractError> {
+        let invalid = || ContractError::InvalidTimestamp { timestamp: self.timestamp.clone() };
+        let numbers = |part: &str, separator: char| part.split(separator)
+            .map(|number| match number.is_empty() || !number.bytes().all(|byte| byte.is_ascii_digit()) {
+                true => None,
+                false => number.parse::<u64>().ok(),
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let (date, time) = self.timestamp.split_once('T').ok_or_else(invalid)?;
+        let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+        let (date, time, fraction) = (numbers(date, '-'), numbers(time, ':'), numbers(fraction, '.'));
+        let (year, month, day, hour, minute, second) = match (date.as_deref(), time.as_deref(), fraction) {
+            (Some(&[year, month, day]), Some(&[hour, minute, second]), Some(_)) => (year, month, day, hour, minute, second),
+            _ => return Err(invalid()),
+        };
+        let valid = (1970..=9999).contains(&year) && (1..=12).contains(&month) && (1..=31).contains(&day)
+            && hour < 24 && minute < 60 && second < 60;
+        if !valid {
+            return Err(invalid());
+        }
+
+        // The days since 1970-01-01, counted in 400 year eras of years that start in March so the leap day is last
+        let year = if month <= 2 { year - 1 } else { year };
+        let (era, year_of_era) = (year / 400, year % 400);
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+
+        Ok(days * 86_400 + hour * 3_600 + minute * 60 + second)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct IASReport {
     pub report: ReportBody,
@@ -40,6 +87,24 @@ pub struct ClaimStruct {
     message: String
 }
 
+impl ClaimStruct {
+    /// A claim for the address given as the 5 bit words of its bech32 data part, which is what enclaves put in
+    /// their claims. `payload::build_claim_payload` builds one from a bech32 address.
+    pub fn new(address: Vec<u8>, message: String) -> Self {
+        ClaimStruct { address, message }
+    }
+
+    /// A claim for the account with this canonical address, which is the data part of its bech32 address
+    pub fn for_account(account: &[u8], message: String) -> Self {
+        ClaimStruct::new(account.to_base32().into_iter().map(|word| word.to_u8()).collect(), message)
+    }
+
+    /// Whether the claim is for the account with this canonical address
+    pub fn is_for(&self, account: &[u8]) -> bool {
+        account.to_base32().into_iter().map(|word| word.to_u8()).eq(self.address.iter().copied())
+    }
+}
+
 /// Given an RSA signature and the signer's exponent + modulus we recover the digest that was signed by the signature.
 pub fn recover_signature_digest(signature: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
     let sig_as_bignum_be = BigUint::from_bytes_be(signature);
@@ -50,3 +115,104 @@ pub fn recover_signature_digest(signature: &[u8], exponent: &[u8], modulus: &[u8
 
     digest_be.to_bytes_be()
 }
+
+/// The DER encoded DigestInfo that PKCS#1 v1.5 puts in front of a SHA-256 digest
+const SHA256_DIGEST_INFO: &[u8] = &hex!("3031300d060960864801650304020105000420");
+
+/// Checks an RSASSA-PKCS1-v1_5 signature over a SHA-256 digest, which is how IAS signs its reports
+pub fn verify_pkcs1v15_sha256(signature: &[u8], exponent: &[u8], modulus: &[u8], digest: &[u8]) -> bool {
+    let modulus: Vec<u8> = modulus.iter().skip_while(|byte| **byte == 0).copied().collect();
+
+    // The signature is exactly as long as the modulus and smaller than it
+    if signature.len() != modulus.len() || BigUint::from_bytes_be(signature) >= BigUint::from_bytes_be(&modulus) {
+        return false;
+    }
+    // There has to be room for at least 8 bytes of padding
+    let padding = match modulus.len().checked_sub(3 + SHA256_DIGEST_INFO.len() + digest.len()) {
+        Some(padding) if padding >= 8 => padding,
+        _ => return false,
+    };
+
+    // The signature is over 00 01 FF .. FF 00 DigestInfo digest, the leading zero doesn't survive the recovery
+    let mut encoded_message = vec![0x01];
+    encoded_message.resize(1 + padding, 0xFF);
+    encoded_message.push(0x00);
+    encoded_message.extend_from_slice(SHA256_DIGEST_INFO);
+    encoded_message.extend_from_slice(digest);
+
+    recover_signature_digest(signature, exponent, &modulus) == encoded_message
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::FromBase32;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use sha2::{Digest, Sha256};
+
+    use crate::ContractError;
+    use crate::fixtures::{CLAIM, IAS_RESPONSE};
+    use crate::intelstructs::{ClaimStruct, IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS, IASReport, verify_pkcs1v15_sha256};
+
+    proptest! {
+        // A signature, a key and a digest can each be anything a claimant or instantiator hands us
+
+        #[test]
+        fn prop_pkcs1v15_refuses_garbage(
+            signature in vec(any::<u8>(), 0..160),
+            exponent in vec(any::<u8>(), 0..4),
+            modulus in vec(any::<u8>(), 0..160),
+            digest in vec(any::<u8>(), 0..40),
+        ) {
+            prop_assert!(!verify_pkcs1v15_sha256(&signature, &exponent, &modulus, &digest));
+        }
+
+        #[test]
+        fn prop_pkcs1v15_refuses_garbage_against_intel(signature in vec(any::<u8>(), 256), report in any::<Vec<u8>>()) {
+            prop_assert!(!verify_pkcs1v15_sha256(&signature, IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS, &Sha256::digest(report)));
+        }
+    }
+
+    #[test]
+    fn test_pkcs1v15_survives_degenerate_keys() {
+        let digest = Sha256::digest(b"report");
+
+        assert!(!verify_pkcs1v15_sha256(&[], &[], &[], &digest));
+        assert!(!verify_pkcs1v15_sha256(&[0], &[1], &[0], &digest));
+        assert!(!verify_pkcs1v15_sha256(&[1], &[], &[1], &digest));
+        // Even moduli and signatures as large as the modulus
+        assert!(!verify_pkcs1v15_sha256(&[0xFF; 64], &[3], &[0xFE; 64], &digest));
+        assert!(!verify_pkcs1v15_sha256(&[0xFE; 64], &[3], &[0xFE; 64], &digest));
+    }
+
+    #[test]
+    fn test_report_timestamps() {
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        assert_eq!(report.report.timestamp_seconds().unwrap(), 1_700_740_025);
+
+        let mut seconds = |timestamp: &str| {
+            report.report.timestamp = timestamp.to_string();
+            report.report.timestamp_seconds()
+        };
+        assert_eq!(seconds("1970-01-01T00:00:00"), Ok(0));
+        assert_eq!(seconds("2024-02-29T23:59:59.999999"), Ok(1_709_251_199));
+        assert_eq!(seconds("2024-03-01T00:00:00"), Ok(1_709_251_200));
+        assert_eq!(seconds("2000-12-31T12:00:00.5"), Ok(978_264_000));
+        for garbage in ["", "2023-11-23", "2023-11-23 11:47:05", "2023-13-01T00:00:00", "2023-11-23T24:00:00", "2023-11-+3T11:47:05", "1969-12-31T23:59:59", "2023-11-23T11:47:05.", "2023-11-23T11:47"] {
+            assert_eq!(seconds(garbage), Err(ContractError::InvalidTimestamp { timestamp: garbage.to_string() }));
+        }
+    }
+
+    #[test]
+    fn test_claims_are_for_the_account_in_their_address() {
+        // The captured claim is for juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r
+        let claim: ClaimStruct = serde_json::from_str(CLAIM).unwrap();
+        let (_, words, _) = bech32::decode("juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r").unwrap();
+        let account = Vec::<u8>::from_base32(&words).unwrap();
+
+        assert!(claim.is_for(&account));
+        assert_eq!(ClaimStruct::for_account(&account, "Hello world!".to_string()), claim);
+        assert!(!claim.is_for(&account[1..]));
+        assert!(!claim.is_for(&[account.as_slice(), &[0]].concat()));
+    }
+}
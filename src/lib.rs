@@ -1,10 +1,23 @@
 pub mod contract;
 mod error;
+pub mod events;
 pub mod helpers;
+mod migrations;
+mod integration_tests;
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+mod mock_ias;
 pub mod msg;
 pub mod state;
 
-mod platforminfoblob;
-mod intelstructs;
+pub mod platforminfoblob;
+pub mod intelstructs;
+pub mod verify;
+pub mod payload;
+#[cfg(feature = "private-queries")]
+pub mod viewing_keys;
+#[cfg(feature = "private-queries")]
+pub mod permits;
 
 pub use crate::error::ContractError;
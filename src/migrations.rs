@@ -0,0 +1,278 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, CanonicalAddr, DepsMut, Env, Order, StdError, StdResult, Uint256};
+use cw_storage_plus::{Item, Map};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::MigrateMsg;
+use crate::state::{Campaign, CAMPAIGNS, ClaimPolicy, DonationInfo, DONATIONS, NEXT_CAMPAIGN_ID, Ownership, OWNERSHIP, REWARD_POTS, RewardCurve, REWARDED, RewardInfo, RewardPot, SEEN_GROUP_IDS};
+
+/// A change to the storage layout and the version that introduced it
+struct MigrationStep {
+    version: &'static str,
+    name: &'static str,
+    run: fn(DepsMut, &Env, &MigrateMsg) -> StdResult<()>
+}
+
+/// Every storage change in the order it was made. Migrating from a version runs every step introduced after it.
+const MIGRATION_STEPS: &[MigrationStep] = &[
+    MigrationStep { version: "0.2.0", name: "campaigns", run: move_into_default_campaign },
+    MigrationStep { version: "0.2.0", name: "typed_group_ids", run: type_seen_group_ids },
+    MigrationStep { version: "0.2.0", name: "ownership", run: add_owner },
+];
+
+/// Runs every migration step introduced after `from` and returns the names of the steps that ran
+pub fn run_migrations(mut deps: DepsMut, env: &Env, msg: &MigrateMsg, from: &Version) -> StdResult<Vec<&'static str>> {
+    let mut ran = vec![];
+
+    for step in MIGRATION_STEPS {
+        let introduced_in = Version::parse(step.version).map_err(|error| StdError::generic_err(error.to_string()))?;
+        if *from < introduced_in {
+            (step.run)(deps.branch(), env, msg)?;
+            ran.push(step.name);
+        }
+    }
+
+    Ok(ran)
+}
+
+/// The single reward pot from before campaigns
+static LEGACY_REWARD_POT: Item<LegacyRewardPot> = Item::new("reward_pot");
+/// Claimant Address => Reward Info, from before campaigns
+static LEGACY_REWARDED: Map<Vec<u8>, RewardInfo> = Map::new("rewardees");
+/// Donor Address => Donation Info, from before campaigns
+static LEGACY_DONATIONS: Map<Vec<u8>, DonationInfo> = Map::new("donations");
+
+#[cw_serde]
+#[allow(non_snake_case)]
+struct LegacyRewardPot {
+    total_reward_units: Uint256,
+    GEIDs: Uint256,
+    pot_of_rewards: Uint256
+}
+
+/// Before campaigns there was a single pot. It becomes the pot of the default campaign, along with its donors and
+/// claimants. The group ids are moved over by `type_seen_group_ids`.
+fn move_into_default_campaign(deps: DepsMut, env: &Env, msg: &MigrateMsg) -> StdResult<()> {
+    let legacy_pot = match LEGACY_REWARD_POT.may_load(deps.storage)? {
+        Some(legacy_pot) => legacy_pot,
+        // Nothing to move, this contract was instantiated with campaigns
+        None => return Ok(())
+    };
+
+    // There was no admin before campaigns
+    let admin = migration_admin(&deps, env, msg)?;
+
+    let campaign = Campaign {
+        name: "default".to_string(),
+        admin,
+        policy: ClaimPolicy::default(),
+        start_time: None,
+        end_time: None,
+        donation_cutoff: None,
+        treasury: None,
+    };
+    let mut pot = RewardPot::new(0, legacy_pot.total_reward_units, RewardCurve::ShareOfPot {});
+    pot.GEIDs = legacy_pot.GEIDs;
+    pot.pot_of_rewards = legacy_pot.pot_of_rewards;
+
+    // The legacy keys are the bare addresses, the new ones sit behind the campaign ID, so we collect before we move
+    let donors = LEGACY_DONATIONS.range(deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>()?;
+    for (donor, donation_info) in donors {
+        pot.total_donations += donation_info.donation_size;
+        pot.unique_donors += 1;
+        LEGACY_DONATIONS.remove(deps.storage, donor.clone());
+        DONATIONS.save(deps.storage, (0, donor), &donation_info)?;
+    }
+
+    let claimants = LEGACY_REWARDED.range(deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>()?;
+    for (claimant, reward_info) in claimants {
+        LEGACY_REWARDED.remove(deps.storage, claimant.clone());
+        REWARDED.save(deps.storage, (0, claimant), &reward_info)?;
+    }
+
+    CAMPAIGNS.save(deps.storage, 0, &campaign)?;
+    REWARD_POTS.save(deps.storage, 0, &pot)?;
+    NEXT_CAMPAIGN_ID.save(deps.storage, &1)?;
+    LEGACY_REWARD_POT.remove(deps.storage);
+
+    Ok(())
+}
+
+/// The admin we're given for what older versions left without one, or the contract's admin
+fn migration_admin(deps: &DepsMut, env: &Env, msg: &MigrateMsg) -> StdResult<Addr> {
+    match &msg.admin {
+        Some(admin) => deps.api.addr_validate(admin),
+        None => {
+            let contract_info = deps.querier.query_wasm_contract_info(env.contract.address.to_string())?;
+            let admin = contract_info.admin.ok_or_else(|| StdError::generic_err("The migration needs an admin, there is no contract admin to take"))?;
+            deps.api.addr_validate(&admin)
+        }
+    }
+}
+
+/// Before ownership the contract had no owner, so the migration's admin becomes the owner
+fn add_owner(deps: DepsMut, env: &Env, msg: &MigrateMsg) -> StdResult<()> {
+    if OWNERSHIP.exists(deps.storage) {
+        return Ok(());
+    }
+
+    let owner = migration_admin(&deps, env, msg)?;
+    OWNERSHIP.save(deps.storage, &Ownership { owner, pending_owner: None })
+}
+
+fn type_seen_group_ids(deps: DepsMut, _env: &Env, _msg: &MigrateMsg) -> StdResult<()> {
+    migrate_seen_group_ids(deps)?;
+
+    Ok(())
+}
+
+/// How `SEEN_GROUP_IDS` looked before its keys and values were typed. Its keys are everything after the namespace,
+/// the 4 byte group id from before campaigns.
+static LEGACY_SEEN_GROUP_IDS: Map<Vec<u8>, LegacyClaimant> = Map::new("seen_group_ids");
+
+/// The claimant stored with a group id, raw canonical bytes until they were typed
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LegacyClaimant {
+    Canonical(Vec<u8>),
+    Typed(Addr)
+}
+
+/// Rewrites every seen group id to a `(campaign id, u32)` key holding the claimant's address and returns how many
+/// were rewritten. Group ids that have already been rewritten are left alone, so migrating twice is harmless.
+pub fn migrate_seen_group_ids(deps: DepsMut) -> StdResult<u64> {
+    // We can't write to the map while ranging over it, so everything to rewrite is collected first
+    let legacy_entries = LEGACY_SEEN_GROUP_IDS
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|entry| match entry {
+            Ok((key, LegacyClaimant::Canonical(claimant))) => Some(Ok((key, claimant))),
+            Ok((_, LegacyClaimant::Typed(_))) => None,
+            Err(error) => Some(Err(error)),
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut migrated = 0;
+
+    for (key, claimant) in legacy_entries {
+        let group_id = legacy_group_id(&key)?;
+        let claimant = deps.api.addr_humanize(&CanonicalAddr::from(claimant))?;
+
+        // The legacy key is shorter than the typed one, so it has to go. Its group id belongs to the default campaign.
+        LEGACY_SEEN_GROUP_IDS.remove(deps.storage, key);
+        SEEN_GROUP_IDS.save(deps.storage, (0, group_id), &claimant)?;
+
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+/// Reads the group id out of a legacy key.
+///
+/// The group id used to be read from the platform info blob in little endian order while the blob holds it in big
+/// endian order, and the misread number was then stored as big endian bytes. Those bytes are the group id's bytes
+/// backwards, so reading them in little endian order gets the group id Intel's tools print.
+fn legacy_group_id(key: &[u8]) -> StdResult<u32> {
+    let bytes: [u8; 4] = key.try_into()
+        .map_err(|_| StdError::generic_err(format!("Can't migrate the seen group id stored under {:?}", key)))?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Api, Order, StdResult, Uint256};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cw_storage_plus::Map;
+    use semver::Version;
+
+    use crate::migrations::{add_owner, LEGACY_DONATIONS, LEGACY_REWARD_POT, LEGACY_REWARDED, LegacyRewardPot, migrate_seen_group_ids, move_into_default_campaign, run_migrations};
+    use crate::msg::MigrateMsg;
+    use crate::state::{CAMPAIGNS, DonationInfo, DONATIONS, NEXT_CAMPAIGN_ID, OWNERSHIP, REWARD_POTS, REWARDED, RewardInfo, SEEN_GROUP_IDS, top_donors};
+
+    #[test]
+    fn test_move_into_default_campaign() {
+        let mut deps = mock_dependencies();
+        let donor = deps.api.addr_canonicalize("donor").unwrap().to_vec();
+        let claimant = deps.api.addr_canonicalize("claimant").unwrap().to_vec();
+
+        let legacy_pot = LegacyRewardPot { total_reward_units: Uint256::from(10u128), GEIDs: Uint256::one(), pot_of_rewards: Uint256::from(90u128) };
+        LEGACY_REWARD_POT.save(deps.as_mut().storage, &legacy_pot).unwrap();
+        let donation_info = DonationInfo { donation_size: Uint256::from(100u128), last_donation: 5, cw20_donations: vec![] };
+        LEGACY_DONATIONS.save(deps.as_mut().storage, donor.clone(), &donation_info).unwrap();
+        let reward_info: RewardInfo = serde_json::from_str(r#"{"reward_shares":"1","claim_time":6}"#).unwrap();
+        LEGACY_REWARDED.save(deps.as_mut().storage, claimant.clone(), &reward_info).unwrap();
+
+        let msg = MigrateMsg { admin: Some("admin".to_string()) };
+        move_into_default_campaign(deps.as_mut(), &mock_env(), &msg).unwrap();
+
+        let campaign = CAMPAIGNS.load(deps.as_ref().storage, 0).unwrap();
+        assert_eq!(campaign.admin, Addr::unchecked("admin"));
+        let pot = REWARD_POTS.load(deps.as_ref().storage, 0).unwrap();
+        assert_eq!(pot.pot_of_rewards, Uint256::from(90u128));
+        assert_eq!(pot.GEIDs, Uint256::one());
+        assert_eq!(pot.total_donations, Uint256::from(100u128));
+        assert_eq!(pot.unique_donors, 1);
+        assert_eq!(NEXT_CAMPAIGN_ID.load(deps.as_ref().storage).unwrap(), 1);
+
+        assert_eq!(DONATIONS.load(deps.as_ref().storage, (0, donor.clone())).unwrap(), donation_info);
+        assert_eq!(REWARDED.load(deps.as_ref().storage, (0, claimant)).unwrap(), reward_info);
+        // The leaderboard index is built as the donors are moved
+        assert_eq!(top_donors(deps.as_ref().storage, 0, None, 10).unwrap().len(), 1);
+        assert!(!LEGACY_REWARD_POT.exists(deps.as_ref().storage));
+
+        // There is nothing left to move the second time around
+        move_into_default_campaign(deps.as_mut(), &mock_env(), &msg).unwrap();
+        assert_eq!(DONATIONS.prefix(0).range(deps.as_ref().storage, None, None, Order::Ascending).count(), 1);
+    }
+
+    #[test]
+    fn test_add_owner() {
+        let mut deps = mock_dependencies();
+
+        // The mock querier knows of no contracts, so there is no contract admin to fall back on
+        assert!(add_owner(deps.as_mut(), &mock_env(), &MigrateMsg { admin: None }).is_err());
+
+        add_owner(deps.as_mut(), &mock_env(), &MigrateMsg { admin: Some("owner".to_string()) }).unwrap();
+        assert_eq!(OWNERSHIP.load(deps.as_ref().storage).unwrap().owner, Addr::unchecked("owner"));
+
+        // An owner that is already there stays
+        add_owner(deps.as_mut(), &mock_env(), &MigrateMsg { admin: Some("someone_else".to_string()) }).unwrap();
+        assert_eq!(OWNERSHIP.load(deps.as_ref().storage).unwrap().owner, Addr::unchecked("owner"));
+    }
+
+    #[test]
+    fn test_run_migrations_runs_newer_steps() {
+        let mut deps = mock_dependencies();
+        let msg = MigrateMsg { admin: Some("admin".to_string()) };
+
+        // Everything runs when migrating from before version tracking
+        let ran = run_migrations(deps.as_mut(), &mock_env(), &msg, &Version::new(0, 0, 0)).unwrap();
+        assert_eq!(ran, vec!["campaigns", "typed_group_ids", "ownership"]);
+
+        let ran = run_migrations(deps.as_mut(), &mock_env(), &msg, &Version::new(0, 2, 0)).unwrap();
+        assert!(ran.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_seen_group_ids() {
+        let mut deps = mock_dependencies();
+        let alice = deps.api.addr_canonicalize("alice").unwrap();
+
+        // Group id 0x00000CB0, misread as 0xB00C0000 and stored big endian
+        let legacy: Map<Vec<u8>, Vec<u8>> = Map::new("seen_group_ids");
+        legacy.save(deps.as_mut().storage, vec![0xb0, 0x0c, 0x00, 0x00], &alice.to_vec()).unwrap();
+
+        assert_eq!(migrate_seen_group_ids(deps.as_mut()).unwrap(), 1);
+
+        let seen: Vec<((u64, u32), Addr)> = SEEN_GROUP_IDS.range(deps.as_ref().storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(seen, vec![((0, 0x0cb0), Addr::unchecked("alice"))]);
+
+        // Nothing is left to migrate the second time around
+        assert_eq!(migrate_seen_group_ids(deps.as_mut()).unwrap(), 0);
+        assert_eq!(SEEN_GROUP_IDS.range(deps.as_ref().storage, None, None, Order::Ascending).count(), 1);
+    }
+}
@@ -0,0 +1,308 @@
+//! A stand in for Intel's attestation service, for tests. It makes up platforms and enclaves with whatever group id,
+//! flags, measurements and report data a test needs and signs IAS shaped reports about them with its own RSA key.
+//! A contract instantiated with `MockIas::signing_key` trusts those reports like it would trust Intel's.
+
+use std::sync::OnceLock;
+
+use cosmwasm_std::{Binary, HexBinary};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+use rsa::traits::PublicKeyParts;
+use sha2::{Digest, Sha256};
+
+use crate::intelstructs::{ClaimStruct, IASReport, ReportBody};
+use crate::payload::report_data;
+use crate::state::SigningKey;
+use crate::verify::signed_report_json;
+
+/// Smaller than IAS's keys, generating keys in unoptimized test builds is slow enough as it is
+const KEY_BITS: usize = 1024;
+
+/// The seed of the key `MockIas::default` signs with
+const DEFAULT_SEED: u64 = 0x1A5;
+
+pub(crate) struct MockIas {
+    key: RsaPrivateKey
+}
+
+impl Default for MockIas {
+    /// The same key for every test, it is only generated once
+    fn default() -> Self {
+        static KEY: OnceLock<RsaPrivateKey> = OnceLock::new();
+
+        MockIas { key: KEY.get_or_init(|| generate_key(DEFAULT_SEED)).clone() }
+    }
+}
+
+fn generate_key(seed: u64) -> RsaPrivateKey {
+    RsaPrivateKey::new(&mut StdRng::seed_from_u64(seed), KEY_BITS).expect("A key can be generated")
+}
+
+impl MockIas {
+    /// A signer with a key of its own, the same seed gives the same key
+    pub fn from_seed(seed: u64) -> Self {
+        MockIas { key: generate_key(seed) }
+    }
+
+    /// The key to instantiate the contract with for it to trust our reports
+    pub fn signing_key(&self) -> SigningKey {
+        SigningKey {
+            modulus: HexBinary::from(self.key.n().to_bytes_be()),
+            exponent: HexBinary::from(self.key.e().to_bytes_be()),
+        }
+    }
+
+    /// Signs a report the way IAS does, with RSASSA-PKCS1-v1_5 over the SHA-256 of the report's JSON
+    pub fn sign(&self, report: ReportBody) -> IASReport {
+        let digest = Sha256::digest(signed_report_json(&report).unwrap().as_bytes());
+        let signature = self.key.sign(Pkcs1v15Sign::new::<Sha256>(), &digest).unwrap();
+
+        IASReport { report, report_sig: Binary::from(signature).to_base64() }
+    }
+
+    /// The report IAS would hand back for a quote from `platform`
+    pub fn report(&self, platform: &MockPlatform) -> IASReport {
+        self.sign(platform.report_body())
+    }
+}
+
+/// A made up platform running a made up enclave, everything about them that ends up in a report
+#[derive(Clone, Debug)]
+pub(crate) struct MockPlatform {
+    pub report_id: String,
+    /// When IAS made the report, in its own format
+    pub timestamp: String,
+    pub quote_status: String,
+    pub advisory_ids: Vec<String>,
+    pub epid_pseudonym: Vec<u8>,
+    pub group_id: u32,
+    pub epid_group_flags: u8,
+    pub tcb_evaluation_flags: u16,
+    pub pse_evaluation_flags: u16,
+    pub cpu_svn: [u8; 16],
+    pub mrenclave: [u8; 32],
+    pub mrsigner: [u8; 32],
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    pub report_data: [u8; 64]
+}
+
+impl Default for MockPlatform {
+    /// An up to date platform in group 0x0CB0 with an "OK" quote status, that didn't put anything in its report data
+    fn default() -> Self {
+        MockPlatform {
+            report_id: "1".to_string(),
+            timestamp: "2023-11-23T11:47:05.757595".to_string(),
+            quote_status: "OK".to_string(),
+            advisory_ids: vec![],
+            epid_pseudonym: vec![0xEE; 128],
+            group_id: 0x0CB0,
+            epid_group_flags: 0,
+            tcb_evaluation_flags: 0,
+            pse_evaluation_flags: 0,
+            cpu_svn: [0x14; 16],
+            mrenclave: [0xE3; 32],
+            mrsigner: [0x1C; 32],
+            isv_prod_id: 0,
+            isv_svn: 0,
+            report_data: [0; 64],
+        }
+    }
+}
+
+impl MockPlatform {
+    /// A platform whose enclave put `claim` in its report data
+    pub fn claiming(claim: &ClaimStruct) -> Self {
+        MockPlatform { report_data: report_data(claim), ..MockPlatform::default() }
+    }
+
+    /// A platform in another group, which is what tells claims apart
+    pub fn in_group(group_id: u32, claim: &ClaimStruct) -> Self {
+        MockPlatform { group_id, epid_pseudonym: group_id.to_be_bytes().repeat(32), ..MockPlatform::claiming(claim) }
+    }
+
+    /// The `sgx_quote_t` without its signature, laid out the way `decode_quote_body` reads it
+    pub fn quote_body(&self) -> Vec<u8> {
+        let mut quote_body = vec![0u8; 432];
+        let mut put = |offset: usize, bytes: &[u8]| quote_body[offset..offset + bytes.len()].copy_from_slice(bytes);
+
+        put(0, &2u16.to_le_bytes());
+        put(2, &1u16.to_le_bytes());
+        put(4, &self.group_id.to_le_bytes());
+        put(48, &self.cpu_svn);
+        put(112, &self.mrenclave);
+        put(176, &self.mrsigner);
+        put(304, &self.isv_prod_id.to_le_bytes());
+        put(306, &self.isv_svn.to_le_bytes());
+        put(368, &self.report_data);
+
+        quote_body
+    }
+
+    /// The TLV encoded platform info blob as IAS hands it out, upper case hex. Its fields are big endian.
+    pub fn platform_info_blob(&self) -> String {
+        let mut blob = vec![0x15, 0x02, 0x00, 0x65];
+        blob.push(self.epid_group_flags);
+        blob.extend_from_slice(&self.tcb_evaluation_flags.to_be_bytes());
+        blob.extend_from_slice(&self.pse_evaluation_flags.to_be_bytes());
+        // The latest TCB's PSVN, PSE ISVSVN, PSDA SVN and the XEID
+        blob.extend_from_slice(&[0; 18 + 2 + 4 + 4]);
+        blob.extend_from_slice(&self.group_id.to_be_bytes());
+        // Nothing checks the blob's own signature
+        blob.extend_from_slice(&[0xAB; 64]);
+
+        HexBinary::from(blob).to_hex().to_uppercase()
+    }
+
+    pub fn report_body(&self) -> ReportBody {
+        ReportBody {
+            id: self.report_id.clone(),
+            timestamp: self.timestamp.clone(),
+            version: 4,
+            epid_pseudonym: Binary::from(self.epid_pseudonym.clone()),
+            advisory_url: "https://security-center.intel.com".to_string(),
+            advisory_ids: self.advisory_ids.clone(),
+            isv_enclave_quote_status: self.quote_status.clone(),
+            platform_info_blob: self.platform_info_blob(),
+            isv_enclave_quote_body: Binary::from(self.quote_body()).to_base64(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Binary, Decimal256, HexBinary};
+
+    use crate::ContractError;
+    use crate::intelstructs::ClaimStruct;
+    use crate::mock_ias::{MockIas, MockPlatform};
+    use crate::platforminfoblob::{convert_platform_info_hex, decode_quote_body};
+    use crate::state::{BonusTier, ClaimPolicy};
+    use crate::verify::verify_report_with_key;
+
+    fn claim() -> ClaimStruct {
+        ClaimStruct::new(vec![1, 2, 3], "Hello world!".to_string())
+    }
+
+    /// The first check a report fails against the policy when the mock signer is trusted
+    fn verify(report: &crate::intelstructs::IASReport, policy: &ClaimPolicy) -> Result<(), ContractError> {
+        let key = MockIas::default().signing_key();
+        verify_report_with_key(report, &claim(), policy, &key.modulus, &key.exponent).into_platform_info().map(|_| ())
+    }
+
+    #[test]
+    fn test_mock_reports_pass_every_check() {
+        let ias = MockIas::default();
+        let platform = MockPlatform::claiming(&claim());
+        let report = ias.report(&platform);
+
+        let key = ias.signing_key();
+        let verification = verify_report_with_key(&report, &claim(), &ClaimPolicy::default(), &key.modulus, &key.exponent);
+        assert!(verification.checks.iter().all(|check| check.passed), "{:?}", verification.checks);
+        assert_eq!(verification.checks.len(), 5);
+    }
+
+    #[test]
+    fn test_mock_platforms_decode_as_they_were_made() {
+        let platform = MockPlatform {
+            epid_group_flags: 0x04,
+            tcb_evaluation_flags: 0x0009,
+            pse_evaluation_flags: 0x0002,
+            isv_svn: 3,
+            ..MockPlatform::in_group(0x0ABC_1234, &claim())
+        };
+
+        let pib = convert_platform_info_hex(&platform.platform_info_blob()).unwrap();
+        assert_eq!(pib.group_id(), 0x0ABC_1234);
+        assert!(!pib.cpusvn_is_up_to_date());
+        assert_eq!(pib.epid_group_flag_names(), vec!["QE_EPID_GROUP_OUT_OF_DATE"]);
+        assert_eq!(pib.tcb_evaluation_flag_names(), vec!["QUOTE_CPUSVN_OUT_OF_DATE", "PLATFORM_CONFIGURATION_NEEDED"]);
+        assert_eq!(pib.pse_evaluation_flag_names(), vec!["EPID_GROUP_ID_BY_PS_HW_GID_REVOKED"]);
+
+        let report = MockIas::default().report(&platform);
+        let quote_body = decode_quote_body(&Binary::from_base64(&report.report.isv_enclave_quote_body).unwrap()).unwrap();
+        assert_eq!(quote_body.epid_group_id, 0x0ABC_1234);
+        assert_eq!(quote_body.mrenclave, HexBinary::from(platform.mrenclave));
+        assert_eq!(quote_body.isv_svn, 3);
+        assert_eq!(quote_body.report_data, HexBinary::from(platform.report_data));
+    }
+
+    #[test]
+    fn test_only_untouched_reports_from_the_trusted_key_pass() {
+        let platform = MockPlatform::claiming(&claim());
+        let report = MockIas::default().report(&platform);
+        assert_eq!(verify(&report, &ClaimPolicy::default()), Ok(()));
+
+        // Anything changed after signing breaks the signature
+        let mut tampered = report.clone();
+        tampered.report.isv_enclave_quote_status = "GROUP_OUT_OF_DATE".to_string();
+        tampered.report.id = "2".to_string();
+        assert_eq!(verify(&tampered, &ClaimPolicy::default()), Err(ContractError::SignatureMismatch {}));
+
+        // So does signing with a key that isn't trusted
+        let untrusted = MockIas::from_seed(7).report(&platform);
+        assert_eq!(verify(&untrusted, &ClaimPolicy::default()), Err(ContractError::SignatureMismatch {}));
+
+        // A signature has to be as long as the key
+        let mut truncated = report;
+        truncated.report_sig = Binary::from(&Binary::from_base64(&truncated.report_sig).unwrap()[1..]).to_base64();
+        assert_eq!(verify(&truncated, &ClaimPolicy::default()), Err(ContractError::SignatureMismatch {}));
+    }
+
+    #[test]
+    fn test_policy_paths() {
+        let ias = MockIas::default();
+        let report_with = |quote_status: &str, mrenclave: [u8; 32]| ias.report(&MockPlatform {
+            quote_status: quote_status.to_string(),
+            mrenclave,
+            ..MockPlatform::claiming(&claim())
+        });
+        let policy = ClaimPolicy {
+            accepted_quote_statuses: vec!["OK".to_string(), "SW_HARDENING_NEEDED".to_string()],
+            accepted_mrenclaves: vec![HexBinary::from([1u8; 32]), HexBinary::from([2u8; 32])],
+            ..ClaimPolicy::default()
+        };
+
+        assert_eq!(verify(&report_with("OK", [1; 32]), &policy), Ok(()));
+        assert_eq!(verify(&report_with("SW_HARDENING_NEEDED", [2; 32]), &policy), Ok(()));
+        assert_eq!(
+            verify(&report_with("GROUP_OUT_OF_DATE", [1; 32]), &policy),
+            Err(ContractError::QuoteStatusNotAccepted { status: "GROUP_OUT_OF_DATE".to_string() }),
+        );
+        assert_eq!(
+            verify(&report_with("OK", [3; 32]), &policy),
+            Err(ContractError::MrenclaveNotAccepted { mrenclave: HexBinary::from([3u8; 32]).to_hex() }),
+        );
+
+        // A revoked enclave is refused even though it is accepted
+        let revoked = ClaimPolicy { revoked_mrenclaves: vec![HexBinary::from([2u8; 32])], ..policy.clone() };
+        assert_eq!(verify(&report_with("OK", [1; 32]), &revoked), Ok(()));
+        assert!(matches!(verify(&report_with("OK", [2; 32]), &revoked), Err(ContractError::MrenclaveNotAccepted { .. })));
+
+        // Reports that claim something else are refused
+        let other_claim = ias.report(&MockPlatform::claiming(&ClaimStruct::new(vec![4], "Hi".to_string())));
+        assert_eq!(verify(&other_claim, &ClaimPolicy::default()), Err(ContractError::PayloadMismatch {}));
+    }
+
+    #[test]
+    fn test_bonus_tiers_follow_the_platform_flags() {
+        let tiers = ClaimPolicy {
+            bonus_tiers: vec![
+                BonusTier { name: "patched".to_string(), quote_status: Some("OK".to_string()), requires_up_to_date_cpusvn: true, reward_shares: Decimal256::percent(200) },
+                BonusTier { name: "fresh".to_string(), quote_status: None, requires_up_to_date_cpusvn: true, reward_shares: Decimal256::percent(150) },
+            ],
+            ..ClaimPolicy::default()
+        };
+        let tier_of = |platform: MockPlatform| {
+            let pib = convert_platform_info_hex(&platform.platform_info_blob()).unwrap();
+            tiers.bonus_tier(&platform.quote_status, pib.cpusvn_is_up_to_date()).map(|tier| tier.name.clone())
+        };
+
+        assert_eq!(tier_of(MockPlatform::default()), Some("patched".to_string()));
+        assert_eq!(tier_of(MockPlatform { quote_status: "SW_HARDENING_NEEDED".to_string(), ..MockPlatform::default() }), Some("fresh".to_string()));
+        assert_eq!(tier_of(MockPlatform { tcb_evaluation_flags: 0x0001, ..MockPlatform::default() }), None);
+        // Other flags don't make the CPUSVN out of date
+        assert_eq!(tier_of(MockPlatform { tcb_evaluation_flags: 0x0008, ..MockPlatform::default() }), Some("patched".to_string()));
+    }
+}
@@ -1,62 +1,532 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint256};
+use cosmwasm_std::{Addr, HexBinary, Uint256};
+use cw20::Cw20ReceiveMsg;
 use crate::intelstructs::{ClaimStruct, IASReport};
-use crate::state::{DonationInfo, RewardInfo};
+#[cfg(feature = "private-queries")]
+use crate::permits::{Permit, PermitPermission};
+use crate::verify::ReportCheck;
+use crate::state::{AttestationRequirements, BonusTier, Campaign, ClaimPolicy, ClaimRecord, DonationEntry, DonationInfo, RewardCurve, PauseState, Payout, RewardInfo, RewardPot, Role};
 
+/// Instantiating creates the default campaign, campaign 0, which takes claims from any enclave at any time
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub(crate) total_reward_shares: Uint256
+    pub(crate) total_reward_shares: Uint256,
+    /// Who is allowed to cancel the default campaign, defaults to the instantiator
+    pub(crate) admin: Option<String>,
+    /// Who owns the contract and grants roles, defaults to the instantiator
+    #[serde(default)]
+    pub(crate) owner: Option<String>,
+    /// The key IAS reports have to be signed with instead of Intel's, so tests can trust their mock signer. Release
+    /// builds don't have it, they only ever trust Intel.
+    #[cfg(test)]
+    #[serde(default)]
+    pub(crate) signing_key: Option<crate::state::SigningKey>,
+    /// How many seconds an attestation counts for with `IsAttested`, defaults to 90 days
+    #[serde(default)]
+    pub(crate) attestation_ttl: Option<u64>
 }
 
 #[cw_serde]
+pub struct MigrateMsg {
+    /// Who administers what older versions left without an admin, defaults to the contract's admin
+    pub admin: Option<String>
+}
+
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     /// For claimants to grab their reward from the pot
     ClaimGEID {
+        /// The campaign whose pot we're claiming from
+        campaign_id: u64,
         // The report that is generated by an enclave, ClaimStruct is encoded in the payload
         report: IASReport,
-        // We'll verify the IASReport and the ClaimStruct together to aware the reward
+        // We'll verify the IASReport and the ClaimStruct together to aware the reward, the claim has to be for the sender
+        to_claim: ClaimStruct,
+    },
+    /// For a platform to prove it runs an enclave on SGX hardware without claiming from any campaign, so other
+    /// contracts can check it with `IsAttested`. Works while claims are paused and renews an attestation that ran out.
+    Attest {
+        /// A report for the claim, it is signed by Intel and counts from when IAS made it
+        report: IASReport,
+        /// The claim the report carries, it has to be for the sender
         to_claim: ClaimStruct,
     },
     /// For a donor to add money to the pot to claim from
     DonateToPot {
+        /// The campaign whose pot we're donating to
+        campaign_id: u64,
+        /// Locks the donation until this unix timestamp, after which what is left of it can be withdrawn.
+        /// Donations without one are irrevocable.
+        expires_at: Option<u64>
+    },
+    /// For a CW20 token contract to hand us tokens that were sent with a `ReceiveMsg` hook
+    Receive(Cw20ReceiveMsg),
+    /// For a donor to take back what is left of their expired donations, or all of them if the campaign was cancelled
+    WithdrawDonation {
+        /// The campaign the donations were made to
+        campaign_id: u64
+    },
+    /// For the campaign's admin to call it off, letting donors withdraw what is left of their donations
+    CancelCampaign {
+        campaign_id: u64
+    },
+    /// For anyone to start a new bounty program with its own pot, policy and set of seen group ids.
+    /// The sender becomes the campaign's admin unless someone else is named.
+    CreateCampaign(CreateCampaignMsg),
+    /// For the campaign's admin to hand out what is left in the pot once the campaign has ended, either to the treasury
+    /// or back to the donors. A cancelled campaign's always go back to the donors. Donors are paid back in batches of at
+    /// most `limit`, call again until the sweep is finished.
+    Sweep {
+        campaign_id: u64,
+        limit: Option<u32>
+    },
+    /// For the owner to start handing the contract over, the new owner has to accept before it is theirs
+    TransferOwnership {
+        new_owner: String
+    },
+    /// For the pending owner to take over the contract
+    AcceptOwnership {},
+    /// For the owner to let someone act in a role
+    GrantRole {
+        role: Role,
+        address: String
+    },
+    /// For the owner to take a role away from someone
+    RevokeRole {
+        role: Role,
+        address: String
+    },
+    /// For the campaign's admin or a policy admin to replace the campaign's claim policy
+    UpdatePolicy {
+        campaign_id: u64,
+        policy: ClaimPolicy
+    },
+    /// For the campaign's admin or a revoker to refuse reports from an enclave that turned out to be broken
+    RevokeMrenclave {
+        campaign_id: u64,
+        mrenclave: HexBinary
+    },
+    /// For a pauser to stop claims, donations or both across every campaign, replacing any earlier pause
+    Pause {
+        claims: bool,
+        donations: bool,
+        /// Why, shown to everyone who runs into the pause
+        reason: Option<String>,
+        /// The unix timestamp from which the pause lifts on its own, it lasts until unpaused when unset
+        until: Option<u64>
+    },
+    /// For a pauser to lift the pause
+    Unpause {},
+    /// For a revoker to take back every attestation an address made, e.g. once its platform turned out to be broken
+    RevokeAttestations {
+        address: String
+    },
+    /// For a claimant to set the key that lets them see their own claims with `MyClaims`
+    #[cfg(feature = "private-queries")]
+    SetViewingKey {
+        key: String,
+        /// Ignored, lets the sender pad the message so its length doesn't give the key away
+        #[serde(default)]
+        padding: Option<String>
+    },
+    /// For the signer of a query permit to stop it from working
+    #[cfg(feature = "private-queries")]
+    RevokePermit {
+        permit_name: String,
+        /// Ignored, lets the sender pad the message
+        #[serde(default)]
+        padding: Option<String>
+    }
+}
+
+#[cw_serde]
+pub struct CreateCampaignMsg {
+    /// A human readable name for the campaign
+    pub name: String,
+    /// Who is allowed to cancel the campaign and sweep its leftovers
+    pub admin: Option<String>,
+    /// The number of reward units that the campaign's pot is split into
+    pub total_reward_units: Uint256,
+    /// How the reward units turn into a share of the pot
+    pub reward_curve: RewardCurve,
+    /// The `isvEnclaveQuoteStatus`es we accept, any status is accepted when empty
+    pub accepted_quote_statuses: Vec<String>,
+    /// The MRENCLAVEs of the enclaves we accept reports from, any enclave is accepted when empty
+    pub accepted_mrenclaves: Vec<HexBinary>,
+    /// The bonuses for claims from well kept platforms, checked in order
+    #[serde(default)]
+    pub bonus_tiers: Vec<BonusTier>,
+    /// The unix timestamp before which claims are refused
+    pub start_time: Option<u64>,
+    /// The unix timestamp from which claims and donations are refused, after which the leftovers can be swept
+    pub end_time: Option<u64>,
+    /// The unix timestamp from which donations are refused
+    pub donation_cutoff: Option<u64>,
+    /// Where the leftovers are swept to, they go back to the donors when there is none
+    pub treasury: Option<String>
+}
 
+/// The hooks a donor can put in the `msg` of a CW20 `Send`
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// For a donor to add CW20 tokens to the pot to claim from
+    DonateToPot {
+        /// The campaign whose pot we're donating to
+        campaign_id: u64,
+        /// Locks the donation until this unix timestamp, after which what is left of it can be withdrawn.
+        expires_at: Option<u64>
     }
 }
 
+// The private-queries build refuses the queries that tell who donated or claimed, and with which group id. Donors and
+// claimants see their own with a viewing key or a permit, everyone else only sees the totals in `PotStats`.
 #[cw_serde]
+#[derive(QueryResponses)]
+#[allow(clippy::large_enum_variant)]
 pub enum QueryMsg {
+    #[returns(FetchDonorsResponse)]
     FetchDonors {
-        /// The page we wish to paginate from
-        page: u64,
-        /// The size of the page we're paginating by
-        page_size: u64
+        campaign_id: u64,
+        /// The donor the page starts after, the `next_start_after` of the previous page
+        start_after: Option<String>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
     },
+    #[returns(FetchRewardedResponse)]
     FetchClaimants {
-        /// The page we wish to paginate from
-        page: u64,
-        /// The size of the page we're paginating by
-        page_size: u64
+        campaign_id: u64,
+        /// The claimant the page starts after, the `next_start_after` of the previous page
+        start_after: Option<String>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
     },
+    #[returns(FetchSeenGroupIds)]
     FetchSeenGuids {
-        /// The page we wish to paginate from
-        page: u64,
-        /// The size of the page we're paginating by
-        page_size: u64
+        campaign_id: u64,
+        /// The group id the page starts after, the `next_start_after` of the previous page
+        start_after: Option<u32>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    #[returns(FetchDonationsResponse)]
+    FetchDonations {
+        campaign_id: u64,
+        /// The donor whose donations we want to see
+        donor: String
+    },
+    #[returns(CampaignResponse)]
+    Campaign {
+        campaign_id: u64
+    },
+    #[returns(DonorResponse)]
+    Donor {
+        campaign_id: u64,
+        address: String
+    },
+    /// A campaign's donors from the biggest native donation to the smallest
+    #[returns(FetchDonorsResponse)]
+    TopDonors {
+        campaign_id: u64,
+        /// The donor the page starts after, the `next_start_after` of the previous page
+        start_after: Option<String>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    #[returns(ClaimantResponse)]
+    Claimant {
+        campaign_id: u64,
+        address: String
+    },
+    #[returns(GroupIdResponse)]
+    GroupId {
+        campaign_id: u64,
+        gid: u32
+    },
+    /// Every claim an address made, across campaigns, oldest first
+    #[returns(ClaimsResponse)]
+    ClaimsByAddress {
+        address: String,
+        /// The claim ID the page starts after, the `next_start_after` of the previous page
+        start_after: Option<u64>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    /// Every claim made from `start_time` up to, but not including, `end_time`, across campaigns, oldest first
+    #[returns(ClaimsResponse)]
+    ClaimsInRange {
+        start_time: u64,
+        end_time: u64,
+        /// The claim ID the page starts after, the `next_start_after` of the previous page
+        start_after: Option<u64>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    /// Everything a dashboard needs to know about a campaign's pot and how it pays out
+    #[returns(PotStatsResponse)]
+    PotStats {
+        campaign_id: u64
+    },
+    /// Runs every check a claim goes through without claiming, along with the reward it would be paid right now
+    #[returns(VerifyReportResponse)]
+    VerifyReport {
+        campaign_id: u64,
+        report: IASReport,
+        to_claim: ClaimStruct,
+        /// Who would be claiming, their earlier claims decide the reward shares
+        claimant: String
+    },
+    /// The owner and everyone who was granted a role
+    #[returns(RolesResponse)]
+    Roles {},
+    /// Whether claims and donations are paused right now, and why
+    #[returns(PauseResponse)]
+    PauseStatus {},
+    #[returns(FetchCampaignsResponse)]
+    FetchCampaigns {
+        /// The campaign ID the page starts after, the `next_start_after` of the previous page
+        start_after: Option<u64>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    /// Whether an address proved it runs an enclave on SGX hardware with a report that meets the requirements, for
+    /// other contracts to gate on. Every accepted claim counts as an attestation.
+    #[returns(IsAttestedResponse)]
+    IsAttested {
+        address: String,
+        #[serde(default)]
+        requirements: AttestationRequirements
+    },
+    /// Every claim an address made, across campaigns, oldest first. Only for whoever knows the address' viewing key.
+    #[cfg(feature = "private-queries")]
+    #[returns(ClaimsResponse)]
+    MyClaims {
+        address: String,
+        /// The key set with `SetViewingKey`
+        key: String,
+        /// The claim ID the page starts after, the `next_start_after` of the previous page
+        start_after: Option<u64>,
+        /// The size of the page, capped at 30
+        limit: Option<u32>
+    },
+    /// An address' donations to a campaign. Only for whoever knows the address' viewing key.
+    #[cfg(feature = "private-queries")]
+    #[returns(FetchDonationsResponse)]
+    MyDonations {
+        address: String,
+        key: String,
+        campaign_id: u64
+    },
+    /// An address' reward from a campaign. Only for whoever knows the address' viewing key.
+    #[cfg(feature = "private-queries")]
+    #[returns(ClaimantResponse)]
+    MyReward {
+        address: String,
+        key: String,
+        campaign_id: u64
+    },
+    /// The same queries for the signer of a permit, without them having to set a viewing key first
+    #[cfg(feature = "private-queries")]
+    #[returns(serde_json::Value)]
+    WithPermit {
+        permit: Permit,
+        query: QueryWithPermit
+    }
+}
+
+/// What a permit's signer can query about themselves, answered the way `MyClaims`, `MyDonations` and `MyReward` are
+#[cfg(feature = "private-queries")]
+#[cw_serde]
+pub enum QueryWithPermit {
+    MyClaims {
+        start_after: Option<u64>,
+        limit: Option<u32>
+    },
+    MyDonations {
+        campaign_id: u64
+    },
+    MyReward {
+        campaign_id: u64
+    }
+}
+
+#[cfg(feature = "private-queries")]
+impl QueryWithPermit {
+    /// What the permit has to allow for the query
+    pub fn permission(&self) -> PermitPermission {
+        match self {
+            QueryWithPermit::MyClaims { .. } | QueryWithPermit::MyReward { .. } => PermitPermission::Claims,
+            QueryWithPermit::MyDonations { .. } => PermitPermission::Donations,
+        }
     }
 }
 
 
 #[cw_serde]
 pub struct FetchDonorsResponse {
-    pub donors: Vec<(Addr, DonationInfo)>
+    pub donors: Vec<(Addr, DonationInfo)>,
+    /// Where the next page starts, there are no more donors when unset
+    pub next_start_after: Option<String>
 }
 
 #[cw_serde]
 pub struct FetchRewardedResponse {
-    pub rewarded: Vec<(Addr, RewardInfo)>
+    pub rewarded: Vec<(Addr, RewardInfo)>,
+    /// Where the next page starts, there are no more claimants when unset
+    pub next_start_after: Option<String>
+}
+
+#[cw_serde]
+pub struct SeenGroupId {
+    pub gid: u32,
+    /// The group id as the big endian hex Intel's tools print, e.g. `00000CB0`
+    pub gid_hex: String,
+    /// Who claimed with the group id
+    pub claimant: Addr
+}
+
+/// Formats a group id the way Intel's tools print it
+pub fn group_id_hex(gid: u32) -> String {
+    format!("{:08X}", gid)
 }
 
 #[cw_serde]
 pub struct FetchSeenGroupIds {
-    pub rewarded: Vec<u32>
-}
\ No newline at end of file
+    pub rewarded: Vec<SeenGroupId>,
+    /// Where the next page starts, there are no more group ids when unset
+    pub next_start_after: Option<u32>
+}
+
+#[cw_serde]
+pub struct DonationEntryInfo {
+    pub donation_id: u64,
+    pub entry: DonationEntry,
+    /// The part of the donation that is still in the pot
+    pub remaining: Uint256,
+    /// The part of the donation that has been handed out to claimants
+    pub consumed: Uint256,
+    /// Whether the donor can withdraw what remains right now
+    pub withdrawable: bool
+}
+
+#[cw_serde]
+pub struct FetchDonationsResponse {
+    pub donations: Vec<DonationEntryInfo>
+}
+
+#[cw_serde]
+pub struct CampaignResponse {
+    pub campaign_id: u64,
+    pub campaign: Campaign,
+    pub pot: RewardPot
+}
+
+#[cw_serde]
+pub struct FetchCampaignsResponse {
+    pub campaigns: Vec<CampaignResponse>,
+    /// Where the next page starts, there are no more campaigns when unset
+    pub next_start_after: Option<u64>
+}
+
+#[cw_serde]
+pub struct DonorResponse {
+    /// The donor's donations to the campaign, unset when they haven't donated
+    pub donation_info: Option<DonationInfo>
+}
+
+#[cw_serde]
+pub struct ClaimantResponse {
+    /// The claimant's reward and everything they have received, unset when they haven't claimed
+    pub reward_info: Option<RewardInfo>
+}
+
+#[cw_serde]
+pub struct GroupIdResponse {
+    pub gid: u32,
+    /// The group id as the big endian hex Intel's tools print
+    pub gid_hex: String,
+    /// Who claimed from the campaign with the group id, unset when nobody has
+    pub claimant: Option<Addr>
+}
+
+#[cw_serde]
+pub struct PotStatsResponse {
+    pub campaign_id: u64,
+    /// Everything that has been donated to the pot
+    pub total_donations: Payout,
+    /// What is in the pot right now
+    pub pot_of_rewards: Payout,
+    /// Everything that has been paid out to claimants
+    pub total_paid_out: Payout,
+    /// The number of claims taken
+    pub claims: Uint256,
+    /// The number of different addresses that have donated
+    pub unique_donors: u64,
+    pub total_reward_units: Uint256,
+    /// The reward units that haven't been handed out yet, unset when the reward curve never runs out of them
+    pub remaining_reward_units: Option<Uint256>,
+    pub reward_curve: RewardCurve,
+    /// The quote statuses, MRENCLAVEs and bonus tiers claims are held to
+    pub policy: ClaimPolicy,
+    /// The modulus of the RSA key that report signatures are checked against
+    pub signing_key_modulus: HexBinary,
+    /// The exponent of the RSA key that report signatures are checked against
+    pub signing_key_exponent: HexBinary
+}
+
+#[cw_serde]
+pub struct VerifyReportResponse {
+    /// Whether the report passed every check
+    pub accepted: bool,
+    /// Every check the report went through, in the order they are run when claiming
+    pub checks: Vec<ReportCheck>,
+    /// The bonus tier the claim would fall into
+    pub bonus_tier: Option<String>,
+    /// What the claim would pay out right now, unset when the report isn't accepted
+    pub reward: Option<Payout>
+}
+
+#[cw_serde]
+pub struct ClaimsResponse {
+    /// (Claim ID, Claim Record)
+    pub claims: Vec<(u64, ClaimRecord)>,
+    /// Where the next page starts, there are no more claims when unset
+    pub next_start_after: Option<u64>
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub owner: Addr,
+    /// Who the contract is being handed over to, unset when it isn't
+    pub pending_owner: Option<Addr>,
+    pub policy_admins: Vec<Addr>,
+    pub revokers: Vec<Addr>,
+    pub pausers: Vec<Addr>
+}
+
+#[cw_serde]
+pub struct IsAttestedResponse {
+    pub attested: bool,
+    /// The most recent attestation that meets the requirements, unset when none does. Always unset on the
+    /// private-queries build, which only tells whether there is one.
+    pub attestation: Option<AttestedEnclave>
+}
+
+/// An attestation without the group id, which would tell everyone which platform the address runs on
+#[cw_serde]
+pub struct AttestedEnclave {
+    pub mrenclave: HexBinary,
+    pub quote_status: String,
+    /// The unix timestamp IAS made the report at
+    pub attested_at: u64,
+    /// The unix timestamp from which the attestation no longer counts
+    pub expires_at: u64
+}
+
+#[cw_serde]
+pub struct PauseResponse {
+    pub claims_paused: bool,
+    pub donations_paused: bool,
+    /// The pause as it was set, unset when nothing was ever paused or the pause was lifted
+    pub pause: Option<PauseState>
+}
@@ -0,0 +1,130 @@
+use bech32::{u5, Variant};
+use cosmwasm_std::{Binary, HexBinary, StdError, StdResult};
+
+use crate::intelstructs::ClaimStruct;
+use crate::platforminfoblob::convert_claim_struct_to_payload;
+
+/// How many bytes of report data an enclave's quote carries
+pub const REPORT_DATA_SIZE: usize = 64;
+
+/// What an enclave operator needs to make a claim: the claim to submit along with the report and the report data the
+/// enclave has to put in its quote for the contract to accept the two together
+pub struct ClaimPayload {
+    pub claim: ClaimStruct,
+    /// The hash of the claim, followed by zeroes. Only the hash is checked, the zeroes are up to the enclave.
+    pub report_data: [u8; REPORT_DATA_SIZE]
+}
+
+impl ClaimPayload {
+    /// The claim the way it is submitted with `ClaimGEID`
+    pub fn claim_json(&self) -> String {
+        // A ClaimStruct is a list of numbers and a string, which always serializes
+        serde_json::to_string(&self.claim).expect("A ClaimStruct always serializes")
+    }
+
+    pub fn report_data_hex(&self) -> String {
+        HexBinary::from(self.report_data.as_slice()).to_hex()
+    }
+
+    pub fn report_data_base64(&self) -> String {
+        Binary::from(self.report_data.as_slice()).to_base64()
+    }
+}
+
+/// Builds the claim for a bech32 address and a message, along with the report data the contract will look for.
+/// The claim holds the address as the 5 bit words of its bech32 data part, so it doesn't depend on the prefix.
+pub fn build_claim_payload(address: &str, message: &str) -> StdResult<ClaimPayload> {
+    let (_, words, variant) = bech32::decode(address)
+        .map_err(|error| StdError::generic_err(format!("{} isn't a bech32 address: {}", address, error)))?;
+    if variant != Variant::Bech32 {
+        return Err(StdError::generic_err(format!("{} is bech32m, addresses are plain bech32", address)));
+    }
+
+    let claim = ClaimStruct::new(words.iter().map(|word| u5::to_u8(*word)).collect(), message.to_string());
+
+    Ok(ClaimPayload { report_data: report_data(&claim), claim })
+}
+
+/// The report data an enclave has to put in its quote to make `claim`, the way the contract's payload check reads it
+pub fn report_data(claim: &ClaimStruct) -> [u8; REPORT_DATA_SIZE] {
+    let mut report_data = [0u8; REPORT_DATA_SIZE];
+    let payload = convert_claim_struct_to_payload(claim.clone());
+    report_data[..payload.len()].copy_from_slice(&payload);
+
+    report_data
+}
+
+/// Golden vectors, the address, the message, the claim to submit and the report data to put in the quote.
+/// The first one is the claim of the report captured from a real enclave.
+#[cfg(test)]
+pub(crate) const GOLDEN_VECTORS: &[(&str, &str, &str, &str)] = &[
+    (
+        "juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4r",
+        "Hello world!",
+        r#"{"address":[31,3,24,28,4,10,7,8,19,25,4,12,13,22,1,12,28,24,30,9,12,8,26,7,5,28,26,25,6,1,24,15],"message":"Hello world!"}"#,
+        "bdcc8e5d4cef55e3708ddc8e7f39a1120e75acf2188839a08cfd0240dd4939a10000000000000000000000000000000000000000000000000000000000000000",
+    ),
+    (
+        "juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+        "",
+        r#"{"address":[0,4,1,0,6,1,0,5,0,24,3,16,16,2,8,10,1,12,6,0,26,3,16,15,2,0,8,17,4,4,24,20],"message":""}"#,
+        "ceafda3ef5fad1d0b323b9ed6993f4fa1856bbc67feb3e0e66a196a1002ece620000000000000000000000000000000000000000000000000000000000000000",
+    ),
+    // The contract hashes the claim's JSON with its backslashes taken out, escaped quotes included
+    (
+        "juno1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5fs09pq",
+        r#"say "hi""#,
+        r#"{"address":[0,4,1,0,6,1,0,5,0,24,3,16,16,2,8,10,1,12,6,0,26,3,16,15,2,0,8,17,4,4,24,20],"message":"say \"hi\""}"#,
+        "4c378a8eee596e08f7740001a1340af2399bb1906e3d5b45a13a5c3b2ef7b4710000000000000000000000000000000000000000000000000000000000000000",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Binary;
+
+    use crate::fixtures::IAS_RESPONSE;
+    use crate::intelstructs::{ClaimStruct, IASReport};
+    use crate::payload::{build_claim_payload, GOLDEN_VECTORS};
+    use crate::platforminfoblob::get_payload_from_quote_body;
+    use crate::state::ClaimPolicy;
+    use crate::verify::verify_report;
+
+
+    #[test]
+    fn test_golden_vectors() {
+        for (address, message, claim_json, report_data) in GOLDEN_VECTORS {
+            let payload = build_claim_payload(address, message).unwrap();
+
+            assert_eq!(payload.claim_json(), *claim_json);
+            assert_eq!(payload.report_data_hex(), *report_data);
+            // What we tell operators to submit is what the contract reads
+            assert_eq!(serde_json::from_str::<ClaimStruct>(claim_json).unwrap(), payload.claim);
+        }
+    }
+
+    #[test]
+    fn test_golden_vector_passes_the_payload_check() {
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let quote_body = Binary::from_base64(&report.report.isv_enclave_quote_body).unwrap();
+        let (address, message, _, _) = GOLDEN_VECTORS[0];
+        let payload = build_claim_payload(address, message).unwrap();
+
+        // The enclave put exactly the report data we build into its quote
+        assert_eq!(&quote_body[368..432], payload.report_data.as_slice());
+        assert_eq!(get_payload_from_quote_body(&quote_body).unwrap(), payload.report_data[..32].to_vec());
+
+        let verification = verify_report(&report, &payload.claim, &ClaimPolicy::default());
+        assert!(verification.checks.iter().any(|check| check.check == "payload" && check.passed));
+
+        // The prefix isn't part of the claim
+        let on_secret = build_claim_payload("secret1lrcuy28gneyvdkpvuc7fvg689u6expc03jcz0r", message).unwrap();
+        assert_eq!(on_secret.report_data, payload.report_data);
+    }
+
+    #[test]
+    fn test_build_claim_payload_refuses_bad_addresses() {
+        assert!(build_claim_payload("juno1lrcuy28gneyvdkpvuc7fvg689u6expc0990s4q", "").is_err());
+        assert!(build_claim_payload("not an address", "").is_err());
+    }
+}
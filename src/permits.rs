@@ -0,0 +1,262 @@
+use bech32::{ToBase32, Variant};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Deps, Empty, ensure, Env, StdError, StdResult};
+use cw_storage_plus::Map;
+use ripemd::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// The permits their signers took back. (Signer, Permit Name) => ()
+pub static REVOKED_PERMITS: Map<(&Addr, &str), Empty> = Map::new("revoked_permits");
+
+/// The prefix of the addresses permits are signed by
+pub const ADDRESS_PREFIX: &str = "secret";
+
+/// A SNIP-24 query permit, in the format secret-toolkit's `Permit` has. Wallets sign it offline, so a user can query
+/// their own data without sending a transaction to set a viewing key first.
+#[cw_serde]
+pub struct Permit {
+    pub params: PermitParams,
+    pub signature: PermitSignature
+}
+
+#[cw_serde]
+pub struct PermitParams {
+    /// What the permit is called, it is revoked by name
+    pub permit_name: String,
+    /// The contracts the permit is good for
+    pub allowed_tokens: Vec<String>,
+    pub chain_id: String,
+    pub permissions: Vec<PermitPermission>
+}
+
+/// What a permit lets its holder see of the signer's data
+#[cw_serde]
+#[derive(Copy)]
+pub enum PermitPermission {
+    /// Their donations
+    Donations,
+    /// Their claims and rewards
+    Claims,
+    /// Everything
+    Owner
+}
+
+#[cw_serde]
+pub struct PermitSignature {
+    pub pub_key: PermitPubKey,
+    pub signature: Binary
+}
+
+#[cw_serde]
+pub struct PermitPubKey {
+    /// Always `tendermint/PubKeySecp256k1`
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// The compressed secp256k1 public key
+    pub value: Binary
+}
+
+impl Permit {
+    /// Checks that the permit was signed for this contract on this chain, that it allows `permission` and that it
+    /// wasn't revoked. Returns who signed it.
+    pub fn validate(&self, deps: Deps, env: &Env, permission: PermitPermission) -> StdResult<Addr> {
+        let params = &self.params;
+        ensure!(params.allowed_tokens.iter().any(|token| *token == env.contract.address.as_str()),
+            StdError::generic_err("The permit isn't for this contract"));
+        ensure!(params.chain_id == env.block.chain_id, StdError::generic_err("The permit isn't for this chain"));
+        ensure!(params.permissions.iter().any(|granted| *granted == permission || *granted == PermitPermission::Owner),
+            StdError::generic_err(format!("The permit doesn't allow {:?}", permission)));
+
+        let pub_key = self.signature.pub_key.value.as_slice();
+        let verified = deps.api.secp256k1_verify(&Sha256::digest(sign_doc(params)?), &self.signature.signature, pub_key)
+            .map_err(|error| StdError::generic_err(format!("The permit's signature can't be checked: {}", error)))?;
+        ensure!(verified, StdError::generic_err("The permit's signature doesn't match"));
+
+        let signer = deps.api.addr_validate(&signer_address(pub_key)?)?;
+        ensure!(!REVOKED_PERMITS.has(deps.storage, (&signer, &params.permit_name)),
+            StdError::generic_err(format!("The permit {} was revoked", params.permit_name)));
+
+        Ok(signer)
+    }
+}
+
+/// The cosmos address of a public key, the bech32 of the RIPEMD-160 of its SHA-256
+pub fn signer_address(pub_key: &[u8]) -> StdResult<String> {
+    let account = Ripemd160::digest(Sha256::digest(pub_key));
+
+    bech32::encode(ADDRESS_PREFIX, account.to_base32(), Variant::Bech32)
+        .map_err(|error| StdError::generic_err(error.to_string()))
+}
+
+// Wallets sign permits as an amino transaction that can never be broadcast, with a zero fee and a single
+// `query_permit` message. The fields are declared in the sorted order amino JSON puts them in.
+
+#[derive(Serialize)]
+struct SignDoc<'a> {
+    account_number: &'static str,
+    chain_id: &'a str,
+    fee: Fee,
+    memo: &'static str,
+    msgs: [PermitMsg<'a>; 1],
+    sequence: &'static str
+}
+
+#[derive(Serialize)]
+struct Fee {
+    amount: [FeeCoin; 1],
+    gas: &'static str
+}
+
+#[derive(Serialize)]
+struct FeeCoin {
+    amount: &'static str,
+    denom: &'static str
+}
+
+#[derive(Serialize)]
+struct PermitMsg<'a> {
+    #[serde(rename = "type")]
+    r#type: &'static str,
+    value: PermitMsgValue<'a>
+}
+
+#[derive(Serialize)]
+struct PermitMsgValue<'a> {
+    allowed_tokens: &'a [String],
+    permissions: &'a [PermitPermission],
+    permit_name: &'a str
+}
+
+/// The bytes a wallet signs for a permit
+pub fn sign_doc(params: &PermitParams) -> StdResult<Vec<u8>> {
+    let sign_doc = SignDoc {
+        account_number: "0",
+        chain_id: &params.chain_id,
+        fee: Fee { amount: [FeeCoin { amount: "0", denom: "uscrt" }], gas: "1" },
+        memo: "",
+        msgs: [PermitMsg {
+            r#type: "query_permit",
+            value: PermitMsgValue {
+                allowed_tokens: &params.allowed_tokens,
+                permissions: &params.permissions,
+                permit_name: &params.permit_name,
+            },
+        }],
+        sequence: "0",
+    };
+
+    serde_json::to_vec(&sign_doc).map_err(|error| StdError::generic_err(error.to_string()))
+}
+
+/// Signs permits the way a wallet does, for tests
+#[cfg(test)]
+pub(crate) mod mock_wallet {
+    use cosmwasm_std::Binary;
+    use k256::ecdsa::{Signature, SigningKey};
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use sha2::{Digest, Sha256};
+
+    use crate::permits::{Permit, PermitParams, PermitPermission, PermitPubKey, PermitSignature, sign_doc, signer_address};
+
+    pub struct MockWallet {
+        key: SigningKey
+    }
+
+    impl MockWallet {
+        pub fn from_seed(seed: u8) -> Self {
+            MockWallet { key: SigningKey::from_slice(&[seed; 32]).expect("Any non zero seed is a valid key") }
+        }
+
+        pub fn pub_key(&self) -> Vec<u8> {
+            self.key.verifying_key().to_encoded_point(true).as_bytes().to_vec()
+        }
+
+        pub fn address(&self) -> String {
+            signer_address(&self.pub_key()).unwrap()
+        }
+
+        pub fn permit(&self, name: &str, contract: &str, chain_id: &str, permissions: Vec<PermitPermission>) -> Permit {
+            let params = PermitParams {
+                permit_name: name.to_string(),
+                allowed_tokens: vec![contract.to_string()],
+                chain_id: chain_id.to_string(),
+                permissions,
+            };
+            let signature: Signature = self.key.sign_prehash(&Sha256::digest(sign_doc(&params).unwrap())).unwrap();
+
+            Permit {
+                params,
+                signature: PermitSignature {
+                    pub_key: PermitPubKey { r#type: "tendermint/PubKeySecp256k1".to_string(), value: Binary::from(self.pub_key()) },
+                    signature: Binary::from(signature.to_bytes().as_slice()),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Binary, Empty};
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+    use crate::permits::{PermitParams, PermitPermission, REVOKED_PERMITS, sign_doc, signer_address};
+    use crate::permits::mock_wallet::MockWallet;
+
+    #[test]
+    fn test_sign_doc_is_what_wallets_sign() {
+        let params = PermitParams {
+            permit_name: "lotto".to_string(),
+            allowed_tokens: vec!["secret1contract".to_string()],
+            chain_id: "secret-4".to_string(),
+            permissions: vec![PermitPermission::Donations, PermitPermission::Owner],
+        };
+
+        assert_eq!(String::from_utf8(sign_doc(&params).unwrap()).unwrap(), concat!(
+            r#"{"account_number":"0","chain_id":"secret-4","fee":{"amount":[{"amount":"0","denom":"uscrt"}],"gas":"1"},"memo":"","#,
+            r#""msgs":[{"type":"query_permit","value":{"allowed_tokens":["secret1contract"],"permissions":["donations","owner"],"permit_name":"lotto"}}],"sequence":"0"}"#,
+        ));
+    }
+
+    #[test]
+    fn test_signer_address() {
+        // The public key of the secp256k1 private key 1, whose hash is the well known 751e76e8199196d454941c45d1b3a323f1433bd6
+        let pub_key = Binary::from_base64("Anm+Zn753LusVaBilc6HCwcCm/zbLc4o2VnygVsW+BeY").unwrap();
+
+        assert_eq!(signer_address(&pub_key).unwrap(), "secret1w508d6qejxtdg4y5r3zarvary0c5xw7kccrnjy");
+    }
+
+    #[test]
+    fn test_permits() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let wallet = MockWallet::from_seed(7);
+        let contract = env.contract.address.as_str();
+        let chain_id = env.block.chain_id.as_str();
+
+        let permit = wallet.permit("lotto", contract, chain_id, vec![PermitPermission::Donations]);
+        assert_eq!(permit.validate(deps.as_ref(), &env, PermitPermission::Donations).unwrap(), Addr::unchecked(wallet.address()));
+        // Only for what it allows
+        assert!(permit.validate(deps.as_ref(), &env, PermitPermission::Claims).is_err());
+        let owner = wallet.permit("owner", contract, chain_id, vec![PermitPermission::Owner]);
+        owner.validate(deps.as_ref(), &env, PermitPermission::Claims).unwrap();
+
+        // Only for this contract on this chain
+        assert!(wallet.permit("lotto", "another contract", chain_id, vec![PermitPermission::Owner]).validate(deps.as_ref(), &env, PermitPermission::Claims).is_err());
+        assert!(wallet.permit("lotto", contract, "pulsar-3", vec![PermitPermission::Owner]).validate(deps.as_ref(), &env, PermitPermission::Claims).is_err());
+
+        // Changing what was signed breaks the signature
+        let mut widened = permit.clone();
+        widened.params.permissions.push(PermitPermission::Owner);
+        assert!(widened.validate(deps.as_ref(), &env, PermitPermission::Claims).unwrap_err().to_string().contains("signature doesn't match"));
+        let mut someone_else = permit.clone();
+        someone_else.signature.pub_key.value = Binary::from(MockWallet::from_seed(8).pub_key());
+        assert!(someone_else.validate(deps.as_ref(), &env, PermitPermission::Donations).is_err());
+
+        // A revoked permit is refused, the others of the signer aren't
+        REVOKED_PERMITS.save(deps.as_mut().storage, (&Addr::unchecked(wallet.address()), "lotto"), &Empty {}).unwrap();
+        assert!(permit.validate(deps.as_ref(), &env, PermitPermission::Donations).unwrap_err().to_string().contains("revoked"));
+        owner.validate(deps.as_ref(), &env, PermitPermission::Donations).unwrap();
+    }
+}
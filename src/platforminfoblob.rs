@@ -1,17 +1,18 @@
 #![allow(non_camel_case_types)]
 
 use std::mem;
-use std::u16;
 
-use cosmwasm_std::{ensure_eq, StdError, StdResult};
-use itertools::Itertools;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{ensure_eq, HexBinary, StdError, StdResult};
 use sha2::{Digest, Sha256};
 use crate::intelstructs::ClaimStruct;
 
+#[allow(dead_code)]
 const SGX_CPUSVN_SIZE: usize = 16;
 const PSVN_SIZE: usize = 18; // sizeof(psvn_t)
 const PSDA_SVN_SIZE: usize = 4;
 const ISVSVN_SIZE: usize = 2;
+#[allow(dead_code)]
 const SGX_PLATFORM_INFO_SIZE: usize = 101;
 
 const QE_EPID_GROUP_REVOKED: u8 = 0x01;
@@ -34,15 +35,18 @@ pub type tcb_psvn_t = [u8; PSVN_SIZE];
 pub type psda_svn_t = [u8; PSDA_SVN_SIZE];
 pub type pse_isvsvn_t = [u8; ISVSVN_SIZE];
 
+// The SGX SDK's CPUSVN and PSVN, kept to document the layout `tcb_psvn_t` flattens
+#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct sgx_cpu_svn_t {
     // 16 bytes
     pub svn: [u8; SGX_CPUSVN_SIZE],
 }
 
+#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
-#[repr(packed)]
+#[repr(C, packed)]
 struct psvn_t {
     // 16 + 2
     pub cpu_svn: sgx_cpu_svn_t,
@@ -50,14 +54,14 @@ struct psvn_t {
 }
 
 #[derive(Copy, Clone, Debug)]
-#[repr(packed)]
+#[repr(C, packed)]
 pub struct sgx_ec256_signature_t {
     pub gx: [u8; 32],
     pub gy: [u8; 32],
 }
 
 #[derive(Copy, Clone, Debug)]
-#[repr(packed)]
+#[repr(C, packed)]
 pub struct platform_info_blob {
     pub sgx_epid_group_flags: u8,
     pub sgx_tcb_evaluation_flags: u16,
@@ -70,7 +74,116 @@ pub struct platform_info_blob {
     pub signature: sgx_ec256_signature_t,
 }
 
-#[repr(packed)]
+impl platform_info_blob {
+    /// The TSV encodes the blob's fields big endian, while transmuting reads them in the machine's byte order
+    pub fn tcb_evaluation_flags(&self) -> u16 {
+        u16::from_be(self.sgx_tcb_evaluation_flags)
+    }
+
+    /// The EPID group id of the platform, as Intel's tools print it
+    pub fn group_id(&self) -> u32 {
+        u32::from_be(self.gid)
+    }
+
+    /// Whether the TCB evaluation found the platform's CPUSVN to be up to date
+    pub fn cpusvn_is_up_to_date(&self) -> bool {
+        self.tcb_evaluation_flags() & QUOTE_CPUSVN_OUT_OF_DATE == 0
+    }
+
+    pub fn pse_evaluation_flags(&self) -> u16 {
+        u16::from_be(self.pse_evaluation_flags)
+    }
+
+    /// The names of the EPID group flags that are set
+    pub fn epid_group_flag_names(&self) -> Vec<&'static str> {
+        let flags = self.sgx_epid_group_flags;
+        [
+            (QE_EPID_GROUP_REVOKED, "QE_EPID_GROUP_REVOKED"),
+            (PERF_REKEY_FOR_QE_EPID_GROUP_AVAILABLE, "PERF_REKEY_FOR_QE_EPID_GROUP_AVAILABLE"),
+            (QE_EPID_GROUP_OUT_OF_DATE, "QE_EPID_GROUP_OUT_OF_DATE"),
+        ].into_iter().filter(|(flag, _)| flags & flag != 0).map(|(_, name)| name).collect()
+    }
+
+    /// The names of the TCB evaluation flags that are set
+    pub fn tcb_evaluation_flag_names(&self) -> Vec<&'static str> {
+        let flags = self.tcb_evaluation_flags();
+        [
+            (QUOTE_CPUSVN_OUT_OF_DATE, "QUOTE_CPUSVN_OUT_OF_DATE"),
+            (QUOTE_ISVSVN_QE_OUT_OF_DATE, "QUOTE_ISVSVN_QE_OUT_OF_DATE"),
+            (QUOTE_ISVSVN_PCE_OUT_OF_DATE, "QUOTE_ISVSVN_PCE_OUT_OF_DATE"),
+            (PLATFORM_CONFIGURATION_NEEDED, "PLATFORM_CONFIGURATION_NEEDED"),
+        ].into_iter().filter(|(flag, _)| flags & flag != 0).map(|(_, name)| name).collect()
+    }
+
+    /// The names of the PSE evaluation flags that are set
+    pub fn pse_evaluation_flag_names(&self) -> Vec<&'static str> {
+        let flags = self.pse_evaluation_flags();
+        [
+            (PSE_ISVSVN_OUT_OF_DATE, "PSE_ISVSVN_OUT_OF_DATE"),
+            (EPID_GROUP_ID_BY_PS_HW_GID_REVOKED, "EPID_GROUP_ID_BY_PS_HW_GID_REVOKED"),
+            (SVN_FROM_PS_HW_SEC_INFO_OUT_OF_DATE, "SVN_FROM_PS_HW_SEC_INFO_OUT_OF_DATE"),
+            (SIGRL_VER_FROM_PS_HW_SIG_RLVER_OUT_OF_DATE, "SIGRL_VER_FROM_PS_HW_SIG_RLVER_OUT_OF_DATE"),
+            (PRIVRL_VER_FROM_PS_HW_PRV_KEY_RLVER_OUT_OF_DATE, "PRIVRL_VER_FROM_PS_HW_PRV_KEY_RLVER_OUT_OF_DATE"),
+        ].into_iter().filter(|(flag, _)| flags & flag != 0).map(|(_, name)| name).collect()
+    }
+}
+
+/// The fields of an `sgx_quote_t` without its signature, which is what IAS hands back as the quote body.
+/// Integers are little endian in the quote.
+#[cw_serde]
+pub struct QuoteBody {
+    pub version: u16,
+    pub sign_type: u16,
+    /// The EPID group id as Intel's tools print it, it is stored little endian unlike in the platform info blob
+    pub epid_group_id: u32,
+    pub qe_svn: u16,
+    pub pce_svn: u16,
+    pub xeid: u32,
+    pub basename: HexBinary,
+    pub cpu_svn: HexBinary,
+    pub misc_select: u32,
+    pub attributes: HexBinary,
+    pub mrenclave: HexBinary,
+    pub mrsigner: HexBinary,
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// The 64 bytes the enclave put in its report, the hash of the claim goes in the first 32
+    pub report_data: HexBinary
+}
+
+/// The size of a quote without its signature
+const QUOTE_BODY_SIZE: usize = 432;
+
+/// Decodes every field of an Intel attestation Quote Body
+pub fn decode_quote_body(quote_body: &[u8]) -> StdResult<QuoteBody> {
+    ensure_eq!(quote_body.len(), QUOTE_BODY_SIZE, StdError::generic_err(format!("A quote body is {} bytes long, this one is {}.", QUOTE_BODY_SIZE, quote_body.len())));
+
+    let bytes = |offset: usize, size: usize| HexBinary::from(&quote_body[offset..offset + size]);
+    let u16_at = |offset: usize| u16::from_le_bytes([quote_body[offset], quote_body[offset + 1]]);
+    let u32_at = |offset: usize| u32::from_le_bytes([quote_body[offset], quote_body[offset + 1], quote_body[offset + 2], quote_body[offset + 3]]);
+
+    Ok(QuoteBody {
+        version: u16_at(0),
+        sign_type: u16_at(2),
+        epid_group_id: u32_at(4),
+        qe_svn: u16_at(8),
+        pce_svn: u16_at(10),
+        xeid: u32_at(12),
+        basename: bytes(16, 32),
+        // The enclave's report body starts here
+        cpu_svn: bytes(48, 16),
+        misc_select: u32_at(64),
+        attributes: bytes(96, 16),
+        mrenclave: bytes(112, 32),
+        mrsigner: bytes(176, 32),
+        isv_prod_id: u16_at(304),
+        isv_svn: u16_at(306),
+        report_data: bytes(368, 64),
+    })
+}
+
+#[allow(dead_code)]
+#[repr(C, packed)]
 struct platform_info {
     #[allow(unused)]
     pub platform_info: [u8; SGX_PLATFORM_INFO_SIZE],
@@ -93,21 +206,21 @@ fn input_is_ok(argv1: &str) -> bool {
 
 /// We take in a platform info blob hex string and convert it to a struct
 pub fn convert_platform_info_hex(blob_hex: &str) -> StdResult<platform_info_blob> {
-    return if input_is_ok(&blob_hex) {
-        let from_hex = hex::decode(blob_hex).unwrap();
-        println!("{:?}", from_hex);
+    if input_is_ok(blob_hex) {
+        let from_hex = hex::decode(blob_hex)
+            .map_err(|error| StdError::generic_err(format!("The passed in hex for the PlatformInfoBlob isn't hex: {}", error)))?;
         let blob_slice = from_hex.as_slice();
 
         convert_platform_info_blob(blob_slice)
     } else {
         Err(StdError::generic_err("The passed in hex for the PlatformInfoBlob was not only alphanumeric characters."))
-    };
+    }
 }
 
 /// We take in a platform info blob as bytes and convert it to the struct
 pub fn convert_platform_info_blob(blob: &[u8]) -> StdResult<platform_info_blob> {
     // Lets make sure that the blob length is 105 bytes long...
-    ensure_eq!(blob.len(), 105 as usize, StdError::generic_err("The passed in PlatformInfoBlob is not of the correct length."));
+    ensure_eq!(blob.len(), 105_usize, StdError::generic_err("The passed in PlatformInfoBlob is not of the correct length."));
 
     // Chop off the TSV header from the blob
     let pib_vec = blob[4..].to_vec();
@@ -123,16 +236,28 @@ pub fn convert_platform_info_blob(blob: &[u8]) -> StdResult<platform_info_blob>
 }
 
 /// Given an Intel attestation Quote Body, we extract the payload from it to compare with a user submitted ClaimStruct.
-pub fn get_payload_from_quote_body(quote_body: &[u8]) -> Vec<u8> {
+pub fn get_payload_from_quote_body(quote_body: &[u8]) -> StdResult<Vec<u8>> {
     const PAYLOAD_OFFSET: usize = 368;
     const PAYLOAD_SIZE: usize = 64;
 
-    let payload = &quote_body[PAYLOAD_OFFSET..PAYLOAD_OFFSET + PAYLOAD_SIZE];
+    let payload = quote_body.get(PAYLOAD_OFFSET..PAYLOAD_OFFSET + PAYLOAD_SIZE)
+        .ok_or_else(|| StdError::generic_err("The quote body is too short to hold a payload."))?;
 
     let payload_vec = Vec::from(payload);
     let spliced_vec = payload_vec.split_at(32).0;
 
-    Vec::from(spliced_vec)
+    Ok(Vec::from(spliced_vec))
+}
+
+/// Given an Intel attestation Quote Body, we extract the MRENCLAVE of the enclave that generated it.
+pub fn get_mrenclave_from_quote_body(quote_body: &[u8]) -> StdResult<Vec<u8>> {
+    const MRENCLAVE_OFFSET: usize = 112;
+    const MRENCLAVE_SIZE: usize = 32;
+
+    match quote_body.get(MRENCLAVE_OFFSET..MRENCLAVE_OFFSET + MRENCLAVE_SIZE) {
+        Some(mrenclave) => Ok(Vec::from(mrenclave)),
+        None => Err(StdError::generic_err("The quote body is too short to hold an MRENCLAVE."))
+    }
 }
 
 /// Given a ClaimStruct we convert it to a string, then we hash it so that it becomes the hash that is in the payload part of an Intel attestation.
@@ -151,43 +276,69 @@ pub fn convert_claim_struct_to_payload(claim_struct: ClaimStruct) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{Binary, CanonicalAddr};
+    use cosmwasm_std::Binary;
     use sha2::{Sha256, Digest};
     use serde::{Deserialize, Serialize};
-    use crate::platforminfoblob::{convert_platform_info_blob, convert_platform_info_hex};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use crate::fixtures::IAS_RESPONSE;
+    use crate::mock_ias::MockPlatform;
+    use crate::platforminfoblob::{convert_platform_info_blob, convert_platform_info_hex, decode_quote_body, get_mrenclave_from_quote_body, get_payload_from_quote_body, PLATFORM_CONFIGURATION_NEEDED};
 
     #[test]
     fn test_convert_platform_info_blob() {
         let pib_bytes: &[u8] = &[21, 2, 0, 101, 0, 0, 8, 0, 0, 20, 20, 2, 4, 1, 128, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 13, 0, 0, 12, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 12, 176, 101, 176, 195, 160, 94, 254, 249, 118, 88, 149, 160, 59, 227, 248, 158, 208, 255, 91, 101, 76, 32, 231, 137, 173, 195, 208, 218, 219, 11, 188, 229, 125, 172, 202, 106, 135, 130, 141, 202, 140, 159, 201, 152, 209, 87, 15, 51, 8, 210, 151, 37, 221, 16, 217, 203, 134, 4, 15, 161, 138, 165, 13, 87, 89];
 
-        let result = convert_platform_info_blob(pib_bytes);
-
-        match result {
-            Ok(pib) => {
-                println!("{:?}", pib);
-            }
-            Err(_) => {}
-        }
+        let pib = convert_platform_info_blob(pib_bytes).unwrap();
 
+        println!("{:?}", pib);
     }
 
     #[test]
     fn test_convert_platform_info_blob_hex() {
         let pib_hex = "150200650000080000141402040180070000000000000000000D00000C000000020000000000000CB065B0C3A05EFEF9765895A03BE3F89ED0FF5B654C20E789ADC3D0DADB0BBCE57DACCA6A87828DCA8C9FC998D1570F3308D29725DD10D9CB86040FA18AA50D5759";
 
-        let result = convert_platform_info_hex(pib_hex);
+        let pib = convert_platform_info_hex(pib_hex).unwrap();
 
-        match result {
-            Ok(pib) => {
-                println!("{:?}", pib)
-            }
-            Err(_) => {}
-        }
+        println!("{:?}", pib)
+    }
+
+    #[test]
+    fn test_tcb_evaluation_flags() {
+        let pib_hex = "150200650000080000141402040180070000000000000000000D00000C000000020000000000000CB065B0C3A05EFEF9765895A03BE3F89ED0FF5B654C20E789ADC3D0DADB0BBCE57DACCA6A87828DCA8C9FC998D1570F3308D29725DD10D9CB86040FA18AA50D5759";
+
+        let pib = convert_platform_info_hex(pib_hex).unwrap();
+
+        // The platform needs configuring but its CPUSVN is fine
+        assert_eq!(pib.tcb_evaluation_flags(), PLATFORM_CONFIGURATION_NEEDED);
+        assert!(pib.cpusvn_is_up_to_date());
+        assert_eq!(pib.group_id(), 0x0cb0);
+
+        assert_eq!(pib.tcb_evaluation_flag_names(), vec!["PLATFORM_CONFIGURATION_NEEDED"]);
+        assert!(pib.epid_group_flag_names().is_empty());
+        assert!(pib.pse_evaluation_flag_names().is_empty());
     }
 
     #[test]
+    fn test_decode_quote_body() {
+        let quote_body = Binary::from_base64("AgABALAMAAAPAA8AAAAAAFHK9aSLRQ1iSu/jKG0xSJQAAAAAAAAAAAAAAAAAAAAAFBQCBwGAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABQAAAAAAAAAHAAAAAAAAAOPC8qW4QNieBprK/8rbZRDvhmpz06nuVxAO1fhkbuS7AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAc8uUpEUEPvz8ZkFapjVh5WlWaLoAJM/f80T0EhGInHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAC9zI5dTO9V43CN3I5/OaESDnWs8hiIOaCM/QJA3Uk5oQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA").unwrap();
+
+        let decoded = decode_quote_body(&quote_body).unwrap();
+
+        assert_eq!(decoded.version, 2);
+        assert_eq!(decoded.sign_type, 1);
+        // The same group the platform info blob names
+        assert_eq!(decoded.epid_group_id, 0x0cb0);
+        assert_eq!(decoded.mrenclave.to_hex(), "e3c2f2a5b840d89e069acaffcadb6510ef866a73d3a9ee57100ed5f8646ee4bb");
+        assert_eq!(decoded.report_data.len(), 64);
+
+        assert!(decode_quote_body(&quote_body[..431]).is_err());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
     fn verify_isvQuoteBodyPayload() {
-        let ias_response = r#"{"report":{"id":"200423264892184291776794534127952959503","timestamp":"2023-11-23T11:47:05.757595","version":4,"epidPseudonym":"+CUyIi74LPqS6M0NF7YrSxLqPdX3MKs6D6LIPqRG/ZEB4WmxZVvxAJwdwg/0m9cYnUUQguLnJotthX645lAogfJgO8Xg5/91lSegwyUKvHmKgtjOHX/YTbVe/wmgWiBdaL+KmarY0Je459Px/FqGLWLsAF7egPAJRd1Xn88Znrs=","advisoryURL":"https://security-center.intel.com","advisoryIDs":["INTEL-SA-00161","INTEL-SA-00219","INTEL-SA-00289","INTEL-SA-00334","INTEL-SA-00615"],"isvEnclaveQuoteStatus":"CONFIGURATION_AND_SW_HARDENING_NEEDED","platformInfoBlob":"150200650000080000141402040180070000000000000000000D00000C000000020000000000000CB07FA713992F17617F506072BA90D3794110D036E2293096E6BF758122D4E6BB68EE3F69B49BA232441025B331F3FA6E6AD1E70E5D8892E5F6565E5C9FCE9B2A24","isvEnclaveQuoteBody":"AgABALAMAAAPAA8AAAAAAFHK9aSLRQ1iSu/jKG0xSJQAAAAAAAAAAAAAAAAAAAAAFBQCBwGAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABQAAAAAAAAAHAAAAAAAAAOPC8qW4QNieBprK/8rbZRDvhmpz06nuVxAO1fhkbuS7AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAc8uUpEUEPvz8ZkFapjVh5WlWaLoAJM/f80T0EhGInHAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAC9zI5dTO9V43CN3I5/OaESDnWs8hiIOaCM/QJA3Uk5oQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"},"reportsig":"VEd3XgpDOEeRzHpKDx61yBUr4t74Z/kQztmOFM4nkaF+muCZe2KoMd1men4R6fYJh4U1DHnrI0U/zym0N4g6olLBfQ1otxb67LV7N1ekSLQtaJw+iQxNfkrqzNSnle3eKi08GAWVIrMRFC0UooCMuUyZoIGXBsjLZ/Jq1dldus2LUBGM5KHhxhAUbbxAdrcc6NO211S3DRAAYkQYkoHMgLZwWm73TS9LLCT/8pFvkiTUXbHyHpVhnbGB9jnkMd6y22iFQrIiQ+LZKcHCuvD5I07oPQqCezCq/rMMCR/6WAcumapLScNm5zndIeWnN8KE+8EG698eCw3GTONiXoE4hw=="}"#;
+        let ias_response = IAS_RESPONSE;
 
         #[derive(Serialize, Deserialize, Clone)]
         pub struct reportBody {
@@ -213,18 +364,6 @@ mod tests {
         let quote_body_binary = report.report.isvEnclaveQuoteBody;
         let quote_body_slice = quote_body_binary.0;
 
-        fn get_payload_from_quote_body(quote_body: &[u8]) -> Vec<u8> {
-            const PAYLOAD_OFFSET: usize = 368;
-            const PAYLOAD_SIZE: usize = 64;
-
-            let payload = &quote_body[PAYLOAD_OFFSET..PAYLOAD_OFFSET + PAYLOAD_SIZE];
-
-            let payload_vec = Vec::from(payload);
-            let spliced_vec = payload_vec.split_at(32).0;
-
-            Vec::from(spliced_vec)
-        }
-
         const QUOTE_BODY_LENGTH: usize = 432;
         const MRENCLAVE_OFFSET: usize = 112;
         const MRSIGNER_OFFSET: usize = 176;
@@ -236,11 +375,12 @@ mod tests {
         assert_eq! {quote_body_slice.len(), QUOTE_BODY_LENGTH}
 
         // Lets extract the MRENCLAVE
-        let MRENCLAVE = &quote_body_slice[MRENCLAVE_OFFSET..MRENCLAVE_OFFSET + 32];
+        let _MRENCLAVE = &quote_body_slice[MRENCLAVE_OFFSET..MRENCLAVE_OFFSET + 32];
 
-        let mrsigner = &quote_body_slice[MRSIGNER_OFFSET..MRSIGNER_OFFSET + 32];
+        let _mrsigner = &quote_body_slice[MRSIGNER_OFFSET..MRSIGNER_OFFSET + 32];
 
         let payload = &quote_body_slice[PAYLOAD_OFFSET..PAYLOAD_OFFSET + PAYLOAD_SIZE];
+        assert_eq!(get_payload_from_quote_body(&quote_body_slice).unwrap(), payload[..32].to_vec());
 
         #[derive(Serialize, Deserialize, Clone)]
         struct claim_struct {
@@ -271,4 +411,50 @@ mod tests {
         assert_eq!(spliced_vec, sha256d_claim);
 
     }
+
+    // The parsers are handed whatever a claimant submits, so they have to turn anything malformed into an error
+    // rather than panic
+
+    proptest! {
+        #[test]
+        fn prop_platform_info_hex_never_panics(blob_hex in "\\PC{0,240}") {
+            prop_assert_eq!(convert_platform_info_hex(&blob_hex).is_ok(), blob_hex.len() == 210 && hex::decode(&blob_hex).is_ok());
+        }
+
+        #[test]
+        fn prop_alphanumeric_platform_info_hex_has_to_be_hex(blob_hex in "[0-9a-zA-Z\u{e9}\u{df}]{100,210}") {
+            prop_assert_eq!(convert_platform_info_hex(&blob_hex).is_ok(), blob_hex.len() == 210 && hex::decode(&blob_hex).is_ok());
+        }
+
+        #[test]
+        fn prop_platform_info_hex_of_any_blob_parses(blob in vec(any::<u8>(), 105)) {
+            prop_assert!(convert_platform_info_hex(&hex::encode(&blob)).is_ok());
+            prop_assert!(convert_platform_info_hex(&hex::encode_upper(&blob)).is_ok());
+        }
+
+        #[test]
+        fn prop_platform_info_blob_needs_105_bytes(blob in vec(any::<u8>(), 0..256)) {
+            prop_assert_eq!(convert_platform_info_blob(&blob).is_ok(), blob.len() == 105);
+        }
+
+        #[test]
+        fn prop_quote_body_parsers_need_enough_bytes(quote_body in vec(any::<u8>(), 0..600)) {
+            prop_assert_eq!(decode_quote_body(&quote_body).is_ok(), quote_body.len() == 432);
+            prop_assert_eq!(get_payload_from_quote_body(&quote_body).is_ok(), quote_body.len() >= 432);
+            prop_assert_eq!(get_mrenclave_from_quote_body(&quote_body).is_ok(), quote_body.len() >= 144);
+        }
+
+        #[test]
+        fn prop_platforms_read_back_as_they_were_made(group_id: u32, epid_group_flags: u8, tcb_evaluation_flags: u16, pse_evaluation_flags: u16) {
+            let platform = MockPlatform { group_id, epid_group_flags, tcb_evaluation_flags, pse_evaluation_flags, ..MockPlatform::default() };
+
+            let pib = convert_platform_info_hex(&platform.platform_info_blob()).unwrap();
+            prop_assert_eq!(pib.group_id(), group_id);
+            prop_assert_eq!(pib.sgx_epid_group_flags, epid_group_flags);
+            prop_assert_eq!(pib.tcb_evaluation_flags(), tcb_evaluation_flags);
+            prop_assert_eq!(pib.pse_evaluation_flags(), pse_evaluation_flags);
+            prop_assert_eq!(pib.cpusvn_is_up_to_date(), tcb_evaluation_flags & 0x0001 == 0);
+            prop_assert_eq!(decode_quote_body(&platform.quote_body()).unwrap().epid_group_id, group_id);
+        }
+    }
 }
@@ -1,17 +1,307 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, CanonicalAddr, Decimal256, StdResult, Storage, Uint256};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, CanonicalAddr, Decimal256, Empty, ensure, HexBinary, Order, StdError, StdResult, Storage, Uint256};
+use cw_storage_plus::{Bound, Index, IndexedMap, IndexList, Item, Map, MultiIndex};
 
+use crate::intelstructs::{IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS};
 
-pub static REWARD_POT: Item<RewardPot> = Item::new("reward_pot");
-pub static REWARDED: Map<Vec<u8>, RewardInfo> = Map::new("rewardees");
 
-pub static DONATIONS: Map<Vec<u8>, DonationInfo> = Map::new("donations");
+/// Every campaign that was created. Campaign ID => Campaign
+pub static CAMPAIGNS: Map<u64, Campaign> = Map::new("campaigns");
+/// The ID the next campaign is saved under
+pub static NEXT_CAMPAIGN_ID: Item<u64> = Item::new("next_campaign_id");
 
-/// Maps a group id back to the person who claimed from the reward pot using it. Group ID => Claimant Address
-pub static SEEN_GROUP_IDS: Map<Vec<u8>, Vec<u8>> = Map::new("seen_group_ids");
+/// The reward pot of every campaign. Campaign ID => Reward Pot
+pub static REWARD_POTS: Map<u64, RewardPot> = Map::new("reward_pots");
+/// (Campaign ID, Claimant Address) => Reward Info
+pub static REWARDED: Map<(u64, Vec<u8>), RewardInfo> = Map::new("rewardees");
 
+/// (Campaign ID, Donor Address) => Donation Info, indexed by donation size for the leaderboard
+pub static DONATIONS: IndexedMap<DonorKey, DonationInfo, DonationIndexes> = IndexedMap::new("donations", DonationIndexes {
+    donation_size: MultiIndex::new(donation_size_index, "donations", "donations__size"),
+});
+
+/// (Campaign ID, Donor Address)
+pub type DonorKey = (u64, Vec<u8>);
+
+pub struct DonationIndexes<'a> {
+    /// (Campaign ID, Donation Size as big endian bytes) => Donors, so a campaign's donors can be listed by how much
+    /// they gave. Big endian bytes sort the same way as the amounts.
+    pub donation_size: MultiIndex<'a, (u64, Vec<u8>), DonationInfo, DonorKey>
+}
+
+impl IndexList<DonationInfo> for DonationIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DonationInfo>> + '_> {
+        let indexes: Vec<&dyn Index<DonationInfo>> = vec![&self.donation_size];
+        Box::new(indexes.into_iter())
+    }
+}
+
+fn donation_size_index(pk: &[u8], donation_info: &DonationInfo) -> (u64, Vec<u8>) {
+    // Every donation record is saved under a (campaign id, donor) key, which starts with the campaign id's length (8)
+    // followed by the id as big endian bytes. `DonorKey::from_slice` panics on keys that are too short, so the id is
+    // read by hand. Should a key ever be malformed, the record is indexed under a campaign id that's never handed out,
+    // so it stays out of every real campaign's donor listing instead of failing the save
+    let campaign_id = pk.strip_prefix(&[0, 8])
+        .and_then(|rest| rest.get(..8))
+        .and_then(|campaign_id| campaign_id.try_into().ok())
+        .map_or(u64::MAX, u64::from_be_bytes);
+
+    (campaign_id, donation_info.donation_size.to_be_bytes().to_vec())
+}
+
+/// Every single donation that was made, so that we can tell how much of it has been consumed by claims.
+/// (Campaign ID, Donor Address, Donation ID) => Donation
+pub static DONATION_ENTRIES: Map<(u64, Vec<u8>, u64), DonationEntry> = Map::new("donation_entries");
+/// The ID the next donation entry is saved under
+pub static NEXT_DONATION_ID: Item<u64> = Item::new("next_donation_id");
+
+/// Maps a group id back to the person who claimed from a campaign's reward pot using it. Every campaign has its own
+/// set of group ids. (Campaign ID, Group ID) => Claimant Address
+pub static SEEN_GROUP_IDS: Map<(u64, u32), Addr> = Map::new("seen_group_ids");
+
+/// Every claim that was paid out, in the order they were made. Claim ID => Claim Record
+pub static CLAIMS: IndexedMap<u64, ClaimRecord, ClaimIndexes> = IndexedMap::new("claims", ClaimIndexes {
+    claimant: MultiIndex::new(|_, record| record.claimant.clone(), "claims", "claims__claimant"),
+    claim_time: MultiIndex::new(|_, record| record.claim_time, "claims", "claims__time"),
+});
+/// The ID the next claim record is saved under
+pub static NEXT_CLAIM_ID: Item<u64> = Item::new("next_claim_id");
+
+pub struct ClaimIndexes<'a> {
+    /// Claimant => Claim IDs
+    pub claimant: MultiIndex<'a, Addr, ClaimRecord, u64>,
+    /// Claim Time => Claim IDs
+    pub claim_time: MultiIndex<'a, u64, ClaimRecord, u64>
+}
+
+impl IndexList<ClaimRecord> for ClaimIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ClaimRecord>> + '_> {
+        let indexes: Vec<&dyn Index<ClaimRecord>> = vec![&self.claimant, &self.claim_time];
+        Box::new(indexes.into_iter())
+    }
+}
+
+/// Who owns the contract, and who they are handing it over to
+pub static OWNERSHIP: Item<Ownership> = Item::new("ownership");
+/// Everyone who was granted a role, the owner holds every role without being granted it. (Role, Holder) => ()
+pub static ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+
+#[cw_serde]
+pub struct Ownership {
+    /// Who grants and revokes roles, and may do everything a role allows
+    pub owner: Addr,
+    /// Who the owner is handing the contract over to, they become the owner once they accept
+    pub pending_owner: Option<Addr>
+}
+
+/// What the owner can allow others to do on their behalf
+#[cw_serde]
+#[derive(Copy)]
+pub enum Role {
+    /// Changes the claim policy of any campaign
+    PolicyAdmin,
+    /// Revokes the MRENCLAVEs of enclaves that turn out to be broken from any campaign
+    Revoker,
+    /// Pauses and unpauses claims and donations
+    Pauser
+}
+
+impl Role {
+    pub const ALL: [Role; 3] = [Role::PolicyAdmin, Role::Revoker, Role::Pauser];
+
+    /// The name the role is stored and reported under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::PolicyAdmin => "policy_admin",
+            Role::Revoker => "revoker",
+            Role::Pauser => "pauser"
+        }
+    }
+}
+
+/// Whether an address holds a role, the owner holds every role
+pub fn has_role(storage: &dyn Storage, address: &Addr, role: Role) -> StdResult<bool> {
+    if OWNERSHIP.load(storage)?.owner == *address {
+        return Ok(true);
+    }
+
+    Ok(ROLES.has(storage, (role.as_str(), address)))
+}
+
+/// Everyone who was granted a role
+pub fn role_holders(storage: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    ROLES.prefix(role.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// The key IAS reports have to be signed with in tests, Intel's when unset
+#[cfg(test)]
+pub static SIGNING_KEY: Item<SigningKey> = Item::new("signing_key");
+
+/// An RSA public key
+#[cw_serde]
+pub struct SigningKey {
+    pub modulus: HexBinary,
+    pub exponent: HexBinary
+}
+
+impl SigningKey {
+    /// The key of Intel's report signing certificate, the one IAS signs its reports with
+    pub fn intel() -> Self {
+        SigningKey { modulus: HexBinary::from(IAS_REPORT_SIGNING_MODULUS), exponent: HexBinary::from(IAS_REPORT_SIGNING_EXPONENT) }
+    }
+
+    pub fn validate(&self) -> StdResult<()> {
+        ensure!(self.modulus.iter().any(|byte| *byte != 0), StdError::generic_err("The signing key needs a modulus"));
+        ensure!(self.exponent.iter().any(|byte| *byte != 0), StdError::generic_err("The signing key needs an exponent"));
+
+        Ok(())
+    }
+}
+
+/// The key the reports that are claimed with have to be signed with. Always Intel's outside of tests, whatever an
+/// older version of the contract stored.
+#[cfg(not(test))]
+pub fn load_signing_key(_storage: &dyn Storage) -> StdResult<SigningKey> {
+    Ok(SigningKey::intel())
+}
+
+/// The key the reports that are claimed with have to be signed with, the mock signer's when a test set it
+#[cfg(test)]
+pub fn load_signing_key(storage: &dyn Storage) -> StdResult<SigningKey> {
+    Ok(SIGNING_KEY.may_load(storage)?.unwrap_or_else(SigningKey::intel))
+}
+
+/// Every address' latest attestation from every enclave, so other contracts can check that an address proved it runs
+/// on SGX hardware. (Address, MRENCLAVE) => Attestation
+pub static ATTESTATIONS: Map<(&Addr, &[u8]), Attestation> = Map::new("attestations");
+/// How many seconds an attestation counts for, `DEFAULT_ATTESTATION_TTL` when unset
+pub static ATTESTATION_TTL: Item<u64> = Item::new("attestation_ttl");
+/// 90 days
+pub const DEFAULT_ATTESTATION_TTL: u64 = 90 * 24 * 60 * 60;
+
+pub fn load_attestation_ttl(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(ATTESTATION_TTL.may_load(storage)?.unwrap_or(DEFAULT_ATTESTATION_TTL))
+}
+
+/// A report that passed every check, made by an enclave for the address it claimed for
+#[cw_serde]
+pub struct Attestation {
+    /// The group id of the platform the enclave ran on
+    pub group_id: u32,
+    pub mrenclave: HexBinary,
+    /// The `isvEnclaveQuoteStatus` of the report
+    pub quote_status: String,
+    /// The unix timestamp IAS made the report at, the block's when the report says it is newer
+    pub attested_at: u64,
+    /// The unix timestamp from which the attestation no longer counts
+    pub expires_at: u64
+}
+
+/// What an attestation has to live up to for `IsAttested`
+#[cw_serde]
+#[derive(Default)]
+pub struct AttestationRequirements {
+    /// The enclave the report has to come from, any enclave will do when unset
+    #[serde(default)]
+    pub mrenclave: Option<HexBinary>,
+    /// The quote statuses that are good enough, any status is when empty
+    #[serde(default)]
+    pub quote_statuses: Vec<String>,
+    /// How many seconds ago the attestation can have been made at most, any unexpired attestation will do when unset
+    #[serde(default)]
+    pub max_age: Option<u64>
+}
+
+impl AttestationRequirements {
+    pub fn accepts(&self, attestation: &Attestation, now: u64) -> bool {
+        let fresh = now < attestation.expires_at
+            && self.max_age.map_or(true, |max_age| now <= attestation.attested_at.saturating_add(max_age));
+        let from_enclave = self.mrenclave.as_ref().map_or(true, |mrenclave| *mrenclave == attestation.mrenclave);
+        let status_accepted = self.quote_statuses.is_empty() || self.quote_statuses.contains(&attestation.quote_status);
+
+        fresh && from_enclave && status_accepted
+    }
+}
+
+/// The most recent attestation of an address that meets the requirements
+pub fn find_attestation(storage: &dyn Storage, address: &Addr, requirements: &AttestationRequirements, now: u64) -> StdResult<Option<Attestation>> {
+    let mut found: Option<Attestation> = None;
+    for attestation in ATTESTATIONS.prefix(address).range(storage, None, None, Order::Ascending) {
+        let (_, attestation) = attestation?;
+        if requirements.accepts(&attestation, now) && found.as_ref().map_or(true, |found| attestation.attested_at > found.attested_at) {
+            found = Some(attestation);
+        }
+    }
+
+    Ok(found)
+}
+
+/// What is paused, unset when nothing is
+pub static PAUSE: Item<PauseState> = Item::new("pause");
+
+/// An emergency stop for claims, donations or both
 #[cw_serde]
+pub struct PauseState {
+    pub claims: bool,
+    pub donations: bool,
+    /// Why they were paused
+    pub reason: Option<String>,
+    pub paused_by: Addr,
+    pub paused_at: u64,
+    /// The unix timestamp from which the pause lifts on its own, it lasts until unpaused when unset
+    pub until: Option<u64>
+}
+
+impl PauseState {
+    /// Whether the pause still holds at `current_time`
+    pub fn is_active(&self, current_time: u64) -> bool {
+        self.until.map_or(true, |until| current_time < until)
+    }
+
+    pub fn claims_paused(&self, current_time: u64) -> bool {
+        self.claims && self.is_active(current_time)
+    }
+
+    pub fn donations_paused(&self, current_time: u64) -> bool {
+        self.donations && self.is_active(current_time)
+    }
+}
+
+/// A single claim, kept around so we know what every claim paid and when
+#[cw_serde]
+pub struct ClaimRecord {
+    pub campaign_id: u64,
+    /// The group id of the platform that claimed
+    pub group_id: u32,
+    pub claimant: Addr,
+    /// What the claim paid out
+    pub payout: Payout,
+    /// The denom of the native part of the payout
+    pub denom: String,
+    /// The `isvEnclaveQuoteStatus` of the report that was claimed with
+    pub quote_status: String,
+    /// The id IAS gave the report that was claimed with
+    pub report_id: String,
+    /// The bonus tier the claim fell into, if any
+    pub bonus_tier: Option<String>,
+    pub block_height: u64,
+    /// The unix timestamp of the claim
+    pub claim_time: u64
+}
+
+/// Saves a claim under the next claim ID and returns that ID
+pub fn save_claim_record(storage: &mut dyn Storage, record: &ClaimRecord) -> StdResult<u64> {
+    let claim_id = NEXT_CLAIM_ID.may_load(storage)?.unwrap_or_default();
+    NEXT_CLAIM_ID.save(storage, &(claim_id + 1))?;
+    CLAIMS.save(storage, claim_id, record)?;
+
+    Ok(claim_id)
+}
+
+#[cw_serde]
+#[allow(non_camel_case_types)]
 pub struct GEID_CLAIM {
     address: Addr,
     claim_message: String,
@@ -22,17 +312,187 @@ pub struct RewardInfo {
     /// Amount of reward shares that this claimant has
     pub(crate) reward_shares: Decimal256,
     /// The unix timestamp of when this claimant claimed
-    pub(crate) claim_time: u64
+    pub(crate) claim_time: u64,
+    /// The name of the bonus tier the claim fell into, if any
+    #[serde(default)]
+    pub(crate) bonus_tier: Option<String>,
+    /// Everything this claimant has been paid out of the pot so far
+    #[serde(default)]
+    pub(crate) received: Payout
+}
+
+#[cw_serde]
+pub struct Campaign {
+    /// A human readable name for the campaign
+    pub(crate) name: String,
+    /// Who is allowed to cancel the campaign
+    pub(crate) admin: Addr,
+    /// What a report has to look like for us to pay out on it
+    pub(crate) policy: ClaimPolicy,
+    /// The unix timestamp before which claims are refused
+    pub(crate) start_time: Option<u64>,
+    /// The unix timestamp from which claims are refused, after which the admin can sweep what is left in the pot
+    pub(crate) end_time: Option<u64>,
+    /// The unix timestamp from which donations are refused
+    #[serde(default)]
+    pub(crate) donation_cutoff: Option<u64>,
+    /// Where the leftovers are swept to once the campaign has ended, they go back to the donors when there is none
+    #[serde(default)]
+    pub(crate) treasury: Option<Addr>
 }
 
 #[cw_serde]
+#[derive(Default)]
+pub struct ClaimPolicy {
+    /// The `isvEnclaveQuoteStatus`es we accept, any status is accepted when empty
+    pub(crate) accepted_quote_statuses: Vec<String>,
+    /// The MRENCLAVEs of the enclaves we accept reports from, any enclave is accepted when empty
+    pub(crate) accepted_mrenclaves: Vec<HexBinary>,
+    /// The bonuses for claims from well kept platforms, the first tier a report qualifies for is applied
+    #[serde(default)]
+    pub(crate) bonus_tiers: Vec<BonusTier>,
+    /// The MRENCLAVEs of enclaves that turned out to be broken, reports from them are refused even when accepted above
+    #[serde(default)]
+    pub(crate) revoked_mrenclaves: Vec<HexBinary>
+}
+
+/// A bonus for operators who keep their platforms patched
+#[cw_serde]
+pub struct BonusTier {
+    /// The name the tier is recorded and reported under
+    pub name: String,
+    /// The `isvEnclaveQuoteStatus` a report needs to be in this tier, any status will do when unset
+    pub quote_status: Option<String>,
+    /// Whether the platform info blob must not flag the CPUSVN as out of date
+    #[serde(default)]
+    pub requires_up_to_date_cpusvn: bool,
+    /// The reward shares a claim in this tier gets, a claim outside of every tier gets one
+    pub reward_shares: Decimal256
+}
+
+impl BonusTier {
+    /// Whether a report with the given quote status, from a platform whose CPUSVN is or isn't up to date, is in the tier
+    pub fn applies_to(&self, quote_status: &str, cpusvn_up_to_date: bool) -> bool {
+        let status_matches = self.quote_status.as_ref().map_or(true, |status| status == quote_status);
+
+        status_matches && (cpusvn_up_to_date || !self.requires_up_to_date_cpusvn)
+    }
+}
+
+impl ClaimPolicy {
+    /// Whether the policy accepts a report with the given quote status
+    pub fn accepts_quote_status(&self, quote_status: &str) -> bool {
+        self.accepted_quote_statuses.is_empty() || self.accepted_quote_statuses.iter().any(|status| status == quote_status)
+    }
+
+    /// Whether the policy accepts a report from the enclave with the given MRENCLAVE
+    pub fn accepts_mrenclave(&self, mrenclave: &[u8]) -> bool {
+        if self.revoked_mrenclaves.iter().any(|revoked| revoked.as_slice() == mrenclave) {
+            return false;
+        }
+
+        self.accepted_mrenclaves.is_empty() || self.accepted_mrenclaves.iter().any(|accepted| accepted.as_slice() == mrenclave)
+    }
+
+    /// The first bonus tier a report with the given quote status and CPUSVN freshness qualifies for
+    pub fn bonus_tier(&self, quote_status: &str, cpusvn_up_to_date: bool) -> Option<&BonusTier> {
+        self.bonus_tiers.iter().find(|tier| tier.applies_to(quote_status, cpusvn_up_to_date))
+    }
+}
+
+impl Default for RewardCurve {
+    fn default() -> Self {
+        RewardCurve::ShareOfPot {}
+    }
+}
+
+impl Campaign {
+    /// Whether claims are taken at `current_time`
+    pub fn is_open(&self, current_time: u64) -> bool {
+        let started = self.start_time.map_or(true, |start_time| current_time >= start_time);
+        let ended = self.end_time.is_some_and(|end_time| current_time >= end_time);
+
+        started && !ended
+    }
+
+    /// Whether the campaign has come to its end at `current_time`
+    pub fn has_ended(&self, current_time: u64) -> bool {
+        self.end_time.is_some_and(|end_time| current_time >= end_time)
+    }
+
+    /// Whether donations are taken at `current_time`
+    pub fn is_taking_donations(&self, current_time: u64) -> bool {
+        let cut_off = self.donation_cutoff.is_some_and(|donation_cutoff| current_time >= donation_cutoff);
+
+        !cut_off && !self.has_ended(current_time)
+    }
+}
+
+/// How much of the pot a claim takes
+#[cw_serde]
+pub enum RewardCurve {
+    /// Every claim takes `1 / total_reward_units` of what is left in the pot, so early claimants get the most
+    ShareOfPot {},
+    /// The pot is split evenly between `total_reward_units` claims, after which no more claims are taken
+    EvenSplit {}
+}
+
+#[cw_serde]
+#[allow(non_snake_case)]
 pub struct RewardPot {
+    /// The campaign this reward pot belongs to
+    #[serde(default)]
+    pub(crate) campaign_id: u64,
     /// The number of reward units that this reward contract contains.
     pub(crate) total_reward_units: Uint256,
+    /// How the reward units turn into a share of the pot
+    #[serde(default)]
+    pub(crate) reward_curve: RewardCurve,
     /// The total amount of GEIDs that have claimed from the contract
     pub(crate) GEIDs: Uint256,
     /// Total amount of donations available to claim from
-    pub(crate) pot_of_rewards: Uint256
+    pub(crate) pot_of_rewards: Uint256,
+    /// CW20 tokens available to claim from, one entry per token contract
+    #[serde(default)]
+    pub(crate) cw20_pot_of_rewards: Vec<TokenBalance>,
+    /// How much of every donation made in the current epoch is still in the pot. Starts at one and every claim
+    /// shrinks it by the share of the pot that the claim took.
+    #[serde(default = "Decimal256::one")]
+    pub(crate) donation_index: Decimal256,
+    /// Bumped whenever a claim empties the pot, donations from earlier epochs have been consumed completely
+    #[serde(default)]
+    pub(crate) donation_epoch: u64,
+    /// Whether the admin has called the campaign off, no more claims or donations are taken and donors can
+    /// withdraw what is left of their donations
+    #[serde(default)]
+    pub(crate) cancelled: bool,
+    /// The total amount of native coins donated, less what was withdrawn
+    #[serde(default)]
+    pub(crate) total_donations: Uint256,
+    /// The total amount of CW20 tokens donated, less what was withdrawn, one entry per token contract
+    #[serde(default)]
+    pub(crate) cw20_total_donations: Vec<TokenBalance>,
+    /// How far along sweeping the leftovers is, `None` until the admin starts the sweep
+    #[serde(default)]
+    pub(crate) sweep: Option<SweepProgress>,
+    /// Everything that has been paid out to claimants
+    #[serde(default)]
+    pub(crate) total_paid_out: Payout,
+    /// The number of different addresses that have donated
+    #[serde(default)]
+    pub(crate) unique_donors: u64
+}
+
+#[cw_serde]
+pub struct SweepProgress {
+    /// What was left in the pot when the sweep started
+    pub(crate) leftovers: Payout,
+    /// What had been donated in total when the sweep started, the leftovers are split between donors by their part of it
+    pub(crate) total_donations: Payout,
+    /// The last donor that got their part of the leftovers, the next batch starts after them. Queries leave it out.
+    pub(crate) last_donor: Option<Vec<u8>>,
+    /// Whether the leftovers have been swept completely
+    pub(crate) finished: bool
 }
 
 #[cw_serde]
@@ -40,13 +500,123 @@ pub struct DonationInfo {
     /// The amount of funds that a certain user has donated
     pub(crate) donation_size: Uint256,
     /// The timestamp(unix timestamp) of the last donation
-    pub(crate) last_donation: u64
+    pub(crate) last_donation: u64,
+    /// The amount of CW20 tokens that a certain user has donated, one entry per token contract
+    #[serde(default)]
+    pub(crate) cw20_donations: Vec<TokenBalance>
+}
+
+#[cw_serde]
+pub struct DonationEntry {
+    /// The CW20 token contract that was donated, `None` when native coins were donated
+    pub(crate) token: Option<Addr>,
+    /// The amount that was donated
+    pub(crate) amount: Uint256,
+    /// The pot's donation index at the time of the donation
+    pub(crate) donation_index: Decimal256,
+    /// The donation epoch the donation was made in
+    pub(crate) epoch: u64,
+    /// The timestamp(unix timestamp) of the donation
+    pub(crate) donation_time: u64,
+    /// The timestamp(unix timestamp) after which the donor can take back what is left of the donation.
+    /// Donations without one are irrevocable unless the campaign gets cancelled.
+    pub(crate) expires_at: Option<u64>
+}
+
+impl DonationEntry {
+    /// The part of the donation that hasn't been handed out to claimants yet.
+    pub fn remaining(&self, pot: &RewardPot) -> Uint256 {
+        if self.epoch != pot.donation_epoch {
+            return Uint256::zero();
+        }
+
+        // Every claim since the donation has shrunk it by the same factor as the donation index
+        let remaining = self.amount.multiply_ratio(pot.donation_index.atomics(), self.donation_index.atomics());
+
+        remaining.min(self.amount)
+    }
+
+    /// Whether the donor is allowed to take back what is left of the donation at `current_time`.
+    pub fn is_withdrawable(&self, pot: &RewardPot, current_time: u64) -> bool {
+        if pot.cancelled {
+            return true;
+        }
+
+        match self.expires_at {
+            Some(expires_at) => current_time >= expires_at,
+            None => false
+        }
+    }
+}
+
+#[cw_serde]
+pub struct TokenBalance {
+    /// The CW20 token contract
+    pub(crate) token: Addr,
+    /// The amount of tokens of that contract
+    pub(crate) amount: Uint256
+}
+
+/// A share of the pot, kept as a fraction so that splitting the pot doesn't lose anything to rounding
+#[cw_serde]
+pub struct Fraction {
+    pub(crate) numerator: Uint256,
+    pub(crate) denominator: Uint256
+}
+
+impl Fraction {
+    /// This fraction of `amount`, rounded down
+    pub fn of(&self, amount: Uint256) -> Uint256 {
+        amount.multiply_ratio(self.numerator, self.denominator)
+    }
+}
+
+/// What a claimant walks away with, the native coins and every CW20 token they have a share of
+#[cw_serde]
+#[derive(Default)]
+pub struct Payout {
+    /// The amount of native coins to send
+    pub(crate) native: Uint256,
+    /// The amount of CW20 tokens to send, one entry per token contract
+    pub(crate) cw20: Vec<TokenBalance>
+}
+
+/// Adds `amount` to the balance of `token` in `balances`, creating the entry if it isn't there yet.
+fn add_to_token_balance(balances: &mut Vec<TokenBalance>, token: &Addr, amount: Uint256) {
+    match balances.iter_mut().find(|balance| balance.token == *token) {
+        Some(balance) => {
+            balance.amount += amount;
+        }
+        None => {
+            balances.push(TokenBalance { token: token.clone(), amount });
+        }
+    }
 }
 
 impl RewardPot {
-    /// The base reward units given out. For each GEID submitted, we make the amount of reward units
-    /// you get less and less. This gives the incentive for people to compete by submitting their unique
-    /// GEID faster.
+    /// An empty reward pot for a campaign that hands out shares of `total_reward_units` following the `reward_curve`.
+    pub fn new(campaign_id: u64, total_reward_units: Uint256, reward_curve: RewardCurve) -> Self {
+        RewardPot {
+            campaign_id,
+            total_reward_units,
+            reward_curve,
+            GEIDs: Uint256::zero(),
+            pot_of_rewards: Uint256::zero(),
+            cw20_pot_of_rewards: vec![],
+            donation_index: Decimal256::one(),
+            donation_epoch: 0,
+            cancelled: false,
+            total_donations: Uint256::zero(),
+            cw20_total_donations: vec![],
+            sweep: None,
+            total_paid_out: Payout::default(),
+            unique_donors: 0,
+        }
+    }
+
+    // The base reward units given out. For each GEID submitted, we make the amount of reward units
+    // you get less and less. This gives the incentive for people to compete by submitting their unique
+    // GEID faster.
     // pub fn reward_units(&self) -> Decimal256 {
     //     Decimal256::one() / self.GEIDs
     // }
@@ -74,23 +644,13 @@ impl RewardPot {
 
     /// A Patron of the GEID gave a donation and we're adding it to the pot! The way this works is that we take the donor's address + amount + donation time
     /// and add them to the leaderboard!
-    pub fn add_donation(&mut self, storage: &mut dyn Storage, donor: CanonicalAddr, amount: Uint256, donation_time: u64) -> StdResult<()> {
-        // Let's see if the donor is an existing one. If they've already donated, we'll grab the record.
-        // Otherwise we are going to create a new one to save.
-        let mut donation_record = match DONATIONS.load(storage, Vec::from(donor.as_slice())) {
-            Ok(record) => {
-                record
-            }
-            Err(_) => {
-                DonationInfo {
-                    donation_size: Uint256::zero(),
-                    last_donation: 0,
-                }
-            }
-        };
+    pub fn add_donation(&mut self, storage: &mut dyn Storage, donor: CanonicalAddr, amount: Uint256, donation_time: u64, expires_at: Option<u64>) -> StdResult<()> {
+        self.count_donor(storage, &donor);
+        let mut donation_record = load_donation_record(storage, self.campaign_id, &donor);
 
         // Increase the amount of rewards in the pot
         self.pot_of_rewards += amount;
+        self.total_donations += amount;
 
         // Increase the donation size by the amount donated
         donation_record.donation_size += amount;
@@ -98,68 +658,768 @@ impl RewardPot {
         donation_record.last_donation = donation_time;
 
         // Save the donation record inside of the Keymap
-        DONATIONS.save(storage, Vec::from(donor.as_slice()), &donation_record).unwrap();
+        DONATIONS.save(storage, (self.campaign_id, Vec::from(donor.as_slice())), &donation_record)?;
+
+        // Keep track of this donation on its own so we know how much of it claims have consumed
+        self.save_donation_entry(storage, &donor, None, amount, donation_time, expires_at)?;
 
         // Tell the world we're happy of the result ^-^
         Ok(())
     }
 
-    /// Returns the amount of rewards that a claimant is able to get from the reward pot.
-    pub fn claimable_share_of_pot(&self, reward_info: RewardInfo) -> Uint256 {
-        let user_shares = reward_info.reward_shares;
-        let user_share_of_rewards = user_shares / self.total_reward_units;
+    /// Same as `add_donation`, but the patron gave us CW20 tokens from the `token` contract instead of native coins.
+    pub fn add_cw20_donation(&mut self, storage: &mut dyn Storage, donor: CanonicalAddr, token: &Addr, amount: Uint256, donation_time: u64, expires_at: Option<u64>) -> StdResult<()> {
+        self.count_donor(storage, &donor);
+        let mut donation_record = load_donation_record(storage, self.campaign_id, &donor);
+
+        // Increase the amount of this token in the pot
+        add_to_token_balance(&mut self.cw20_pot_of_rewards, token, amount);
+        add_to_token_balance(&mut self.cw20_total_donations, token, amount);
+
+        // Increase the amount of this token the donor has given
+        add_to_token_balance(&mut donation_record.cw20_donations, token, amount);
+        // Set the last donation time to the one that was given
+        donation_record.last_donation = donation_time;
+
+        DONATIONS.save(storage, (self.campaign_id, Vec::from(donor.as_slice())), &donation_record)?;
+
+        self.save_donation_entry(storage, &donor, Some(token.clone()), amount, donation_time, expires_at)?;
+
+        Ok(())
+    }
+
+    /// Counts the donor if this is their first donation to the campaign. Donation records are never removed, so
+    /// every donor is counted once.
+    fn count_donor(&mut self, storage: &dyn Storage, donor: &CanonicalAddr) {
+        if !DONATIONS.has(storage, (self.campaign_id, Vec::from(donor.as_slice()))) {
+            self.unique_donors += 1;
+        }
+    }
+
+    /// The reward units that haven't been handed out yet. Only an even split runs out of them, every claim on a
+    /// share of the pot takes from what is left, so there are always more.
+    pub fn remaining_reward_units(&self) -> Option<Uint256> {
+        match self.reward_curve {
+            RewardCurve::ShareOfPot {} => None,
+            RewardCurve::EvenSplit {} => Some(self.total_reward_units.checked_sub(self.GEIDs).unwrap_or_default()),
+        }
+    }
 
-        let user_rewards_from_pot = self.pot_of_rewards * user_share_of_rewards;
+    /// Saves a single donation under the next donation ID, along with the current donation index.
+    fn save_donation_entry(&self, storage: &mut dyn Storage, donor: &CanonicalAddr, token: Option<Addr>, amount: Uint256, donation_time: u64, expires_at: Option<u64>) -> StdResult<()> {
+        let donation_id = NEXT_DONATION_ID.may_load(storage)?.unwrap_or_default();
+        NEXT_DONATION_ID.save(storage, &(donation_id + 1))?;
 
-        return user_rewards_from_pot
+        let entry = DonationEntry {
+            token,
+            amount,
+            donation_index: self.donation_index,
+            epoch: self.donation_epoch,
+            donation_time,
+            expires_at,
+        };
+
+        DONATION_ENTRIES.save(storage, (self.campaign_id, Vec::from(donor.as_slice()), donation_id), &entry)
+    }
+
+    /// A donor wants back what is left of their donations that they are allowed to withdraw. The entries are removed,
+    /// taken out of the pot and out of the donor's record, and we return what needs to be sent back to them.
+    pub fn withdraw_donations(&mut self, storage: &mut dyn Storage, donor: CanonicalAddr, current_time: u64) -> StdResult<Payout> {
+        let entries: Vec<(u64, DonationEntry)> = DONATION_ENTRIES
+            .prefix((self.campaign_id, Vec::from(donor.as_slice())))
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+
+        let mut withdrawn = Payout { native: Uint256::zero(), cw20: vec![] };
+        let mut donation_record = load_donation_record(storage, self.campaign_id, &donor);
+
+        for (donation_id, entry) in entries {
+            if !entry.is_withdrawable(self, current_time) {
+                continue;
+            }
+
+            let remaining = entry.remaining(self);
+
+            match &entry.token {
+                None => {
+                    // Claims round down, so the pot may hold a little less than what is attributed to the donors
+                    let remaining = remaining.min(self.pot_of_rewards);
+                    self.pot_of_rewards -= remaining;
+                    donation_record.donation_size -= remaining.min(donation_record.donation_size);
+                    self.total_donations -= remaining.min(self.total_donations);
+                    withdrawn.native += remaining;
+                }
+                Some(token) => {
+                    let remaining = remaining.min(take_from_token_balance(&mut self.cw20_pot_of_rewards, token, remaining));
+                    take_from_token_balance(&mut donation_record.cw20_donations, token, remaining);
+                    take_from_token_balance(&mut self.cw20_total_donations, token, remaining);
+                    add_to_token_balance(&mut withdrawn.cw20, token, remaining);
+                }
+            }
+
+            DONATION_ENTRIES.remove(storage, (self.campaign_id, Vec::from(donor.as_slice()), donation_id));
+        }
+
+        DONATIONS.save(storage, (self.campaign_id, Vec::from(donor.as_slice())), &donation_record)?;
+
+        Ok(withdrawn)
+    }
+
+    /// Everything that is in the pot right now, leaving out tokens that have run dry
+    pub fn balance(&self) -> Payout {
+        Payout {
+            native: self.pot_of_rewards,
+            cw20: self.cw20_pot_of_rewards.iter().filter(|balance| !balance.amount.is_zero()).cloned().collect(),
+        }
+    }
+
+    /// The campaign is over and everything left in the pot goes to the treasury.
+    pub fn sweep_to_treasury(&mut self) -> Payout {
+        let leftovers = self.balance();
+
+        self.deduct_payout(&leftovers);
+        self.sweep = Some(SweepProgress {
+            leftovers: leftovers.clone(),
+            total_donations: Payout { native: self.total_donations, cw20: self.cw20_total_donations.clone() },
+            last_donor: None,
+            finished: true,
+        });
+
+        leftovers
+    }
+
+    /// The campaign is over and what is left in the pot goes back to the donors, split by how much each of them donated.
+    /// There can be a lot of donors, so this goes through at most `limit` of them at a time, picking up after the last
+    /// donor of the previous batch. Returns every donor of the batch along with their part of the leftovers.
+    pub fn sweep_to_donors(&mut self, storage: &dyn Storage, limit: usize) -> StdResult<Vec<(Vec<u8>, Payout)>> {
+        let mut progress = match self.sweep.take() {
+            Some(progress) => progress,
+            // This is the first batch, so we take note of what there is to split
+            None => SweepProgress {
+                leftovers: Payout {
+                    native: self.pot_of_rewards,
+                    cw20: self.cw20_pot_of_rewards.iter().filter(|balance| !balance.amount.is_zero()).cloned().collect(),
+                },
+                total_donations: Payout { native: self.total_donations, cw20: self.cw20_total_donations.clone() },
+                last_donor: None,
+                finished: false,
+            }
+        };
+
+        let start_after = progress.last_donor.clone().map(Bound::exclusive);
+        // We look one donor past the batch to know whether this is the last one
+        let mut donors: Vec<(Vec<u8>, DonationInfo)> = DONATIONS
+            .prefix(self.campaign_id)
+            .range(storage, start_after, None, Order::Ascending)
+            .take(limit + 1)
+            .collect::<StdResult<_>>()?;
+        progress.finished = donors.len() <= limit;
+        donors.truncate(limit);
+
+        let mut swept = Vec::with_capacity(donors.len());
+
+        for (donor, donation_record) in &donors {
+            let native = share_of_leftovers(progress.leftovers.native, donation_record.donation_size, progress.total_donations.native);
+
+            let cw20 = progress.leftovers.cw20.iter()
+                .map(|leftover| {
+                    let donated = token_balance(&donation_record.cw20_donations, &leftover.token);
+                    let total_donated = token_balance(&progress.total_donations.cw20, &leftover.token);
+                    TokenBalance { token: leftover.token.clone(), amount: share_of_leftovers(leftover.amount, donated, total_donated) }
+                })
+                .filter(|balance| !balance.amount.is_zero())
+                .collect();
+
+            let payout = Payout { native, cw20 };
+            self.deduct_payout(&payout);
+            swept.push((donor.clone(), payout));
+        }
+
+        progress.last_donor = donors.last().map(|(donor, _)| donor.clone()).or(progress.last_donor);
+        self.sweep = Some(progress);
+
+        Ok(swept)
+    }
+
+    /// Returns the share of what is left in the pot that a claimant with `reward_shares` gets, following the reward curve.
+    pub fn share_of_pot(&self, reward_shares: Decimal256) -> StdResult<Fraction> {
+        let units = match self.reward_curve {
+            RewardCurve::ShareOfPot { } => {
+                self.total_reward_units
+            }
+            RewardCurve::EvenSplit { } => {
+                // Whatever is left gets split between the reward units that haven't been claimed yet
+                let units_left = self.total_reward_units.checked_sub(self.GEIDs).unwrap_or_default();
+                ensure!(!units_left.is_zero(), StdError::generic_err("Every reward unit of this campaign has been handed out"));
+                units_left
+            }
+        };
+
+        // Decimals are stored as atomics over 10^18, so reward_shares / units = atomics / (units * 10^18)
+        let denominator = units.checked_mul(Decimal256::one().atomics())?;
+        let numerator = reward_shares.atomics().min(denominator);
+
+        Ok(Fraction { numerator, denominator })
+    }
+
+    /// Returns the amount of rewards that a claimant taking `share_of_pot` is able to get from the reward pot.
+    pub fn claimable_share_of_pot(&self, share_of_pot: &Fraction) -> Payout {
+        let user_rewards_from_pot = share_of_pot.of(self.pot_of_rewards);
+
+        // Every CW20 token in the pot is split the same way as the native coins
+        let user_cw20_rewards_from_pot = self.cw20_pot_of_rewards.iter()
+            .map(|balance| TokenBalance { token: balance.token.clone(), amount: share_of_pot.of(balance.amount) })
+            .filter(|balance| !balance.amount.is_zero())
+            .collect();
+
+        Payout {
+            native: user_rewards_from_pot,
+            cw20: user_cw20_rewards_from_pot,
+        }
+    }
+
+    /// What `claim_rewards` would pay the claimant right now, without touching the pot or the storage.
+    pub fn preview_claim(&self, storage: &dyn Storage, claimant: &CanonicalAddr, bonus_tier: Option<&BonusTier>) -> StdResult<Payout> {
+        let reward_shares = match REWARDED.may_load(storage, (self.campaign_id, Vec::from(claimant.as_slice())))? {
+            Some(reward_info) => reward_info.reward_shares,
+            None => bonus_tier.map_or(Decimal256::one(), |tier| tier.reward_shares),
+        };
+        let share_of_pot = self.share_of_pot(reward_shares)?;
+
+        Ok(self.claimable_share_of_pot(&share_of_pot))
     }
 
     /// A claimant would like to claim their reward from the pot! Since they were kind enough to provide a GEID we've never
     /// seen before lets reward them.
-    pub fn claim_rewards(&mut self, storage: &mut dyn Storage, claimant: CanonicalAddr, claim_time: u64) -> Uint256 {
-        let claimant = match REWARDED.load(storage, Vec::from(claimant.as_slice())) {
-            Ok(reward_info) => {
+    /// A claim in a bonus tier gets the tier's reward shares instead of a single one.
+    pub fn claim_rewards(&mut self, storage: &mut dyn Storage, claimant: CanonicalAddr, claim_time: u64, bonus_tier: Option<&BonusTier>) -> StdResult<Payout> {
+        let key = (self.campaign_id, Vec::from(claimant.as_slice()));
+        let mut reward_info = match REWARDED.may_load(storage, key.clone())? {
+            Some(reward_info) => {
                 reward_info
             }
-            Err(_) => {
-                let reward_info = RewardInfo {
-                    reward_shares: Decimal256::from_ratio(1u128, 1u128),
+            None => {
+                // We've never seen this claimant before, so they get a new RewardInfo
+                RewardInfo {
+                    reward_shares: bonus_tier.map_or(Decimal256::one(), |tier| tier.reward_shares),
                     claim_time,
-                };
-                // Save the new claimants reward info since we've never seen them before
-                REWARDED.save(storage, Vec::from(claimant.as_slice()), &reward_info).unwrap();
-                // return the newly created RewardInfo
-                reward_info
+                    bonus_tier: bonus_tier.map(|tier| tier.name.clone()),
+                    received: Payout::default(),
+                }
             }
         };
+        let share_of_pot = self.share_of_pot(reward_info.reward_shares)?;
+
         // the amount of rewards that the user should be able to claim from the pot
-        let amount_to_reward = self.claimable_share_of_pot(claimant);
+        let payout = self.claimable_share_of_pot(&share_of_pot);
 
-        amount_to_reward
+        // keep track of everything the claimant has been paid, so they can look it up later
+        reward_info.received.native += payout.native;
+        for balance in &payout.cw20 {
+            add_to_token_balance(&mut reward_info.received.cw20, &balance.token, balance.amount);
+        }
+        REWARDED.save(storage, key, &reward_info)?;
+
+        self.total_paid_out.native += payout.native;
+        for balance in &payout.cw20 {
+            add_to_token_balance(&mut self.total_paid_out.cw20, &balance.token, balance.amount);
+        }
+
+        // every donation just lost the same share of what was left of it
+        self.consume_donations(&share_of_pot);
+
+        // one more GEID has claimed from the pot
+        self.GEIDs += Uint256::one();
+
+        Ok(payout)
+    }
+
+    /// Shrinks the donation index by the share of the pot that a claim took. When the pot was emptied all donations so far
+    /// have been consumed, so we start a new epoch rather than dividing by zero on the next donation.
+    fn consume_donations(&mut self, share_of_pot: &Fraction) {
+        let share_left = Fraction {
+            numerator: share_of_pot.denominator - share_of_pot.numerator,
+            denominator: share_of_pot.denominator,
+        };
+
+        self.donation_index = Decimal256::new(share_left.of(self.donation_index.atomics()));
+
+        if self.donation_index.is_zero() {
+            self.donation_epoch += 1;
+            self.donation_index = Decimal256::one();
+        }
+    }
+
+    /// Takes what was paid out to a claimant out of the pot.
+    pub fn deduct_payout(&mut self, payout: &Payout) {
+        self.pot_of_rewards -= payout.native;
+
+        for paid in &payout.cw20 {
+            if let Some(balance) = self.cw20_pot_of_rewards.iter_mut().find(|balance| balance.token == paid.token) {
+                balance.amount -= paid.amount;
+            }
+        }
+    }
+
+    /// The pot as queries show it, without the canonical address of the donor a sweep stopped at
+    pub fn for_response(mut self) -> Self {
+        if let Some(progress) = &mut self.sweep {
+            progress.last_donor = None;
+        }
+
+        self
+    }
+}
+
+/// The balance of `token` in `balances`, zero if it isn't there.
+fn token_balance(balances: &[TokenBalance], token: &Addr) -> Uint256 {
+    balances.iter().find(|balance| balance.token == *token).map(|balance| balance.amount).unwrap_or_default()
+}
+
+/// A donor's part of the leftovers, following their part of the total donations.
+fn share_of_leftovers(leftovers: Uint256, donated: Uint256, total_donated: Uint256) -> Uint256 {
+    if total_donated.is_zero() {
+        return Uint256::zero();
+    }
+
+    leftovers.multiply_ratio(donated.min(total_donated), total_donated)
+}
+
+/// Takes up to `amount` off the balance of `token` in `balances`, returning how much could be taken.
+fn take_from_token_balance(balances: &mut [TokenBalance], token: &Addr, amount: Uint256) -> Uint256 {
+    match balances.iter_mut().find(|balance| balance.token == *token) {
+        Some(balance) => {
+            let taken = amount.min(balance.amount);
+            balance.amount -= taken;
+            taken
+        }
+        None => Uint256::zero()
+    }
+}
+
+/// A campaign's donors from the biggest donation to the smallest, starting after `start_after`.
+pub fn top_donors(storage: &dyn Storage, campaign_id: u64, start_after: Option<&CanonicalAddr>, limit: usize) -> StdResult<Vec<(Vec<u8>, DonationInfo)>> {
+    // The index is ordered by (donation size, donor), so we need the donor's current donation size to start after them
+    let max = match start_after {
+        Some(donor) => {
+            let donor = Vec::from(donor.as_slice());
+            let donation_info = DONATIONS.load(storage, (campaign_id, donor.clone()))?;
+            Some(Bound::exclusive((donation_info.donation_size.to_be_bytes().to_vec(), (campaign_id, donor))))
+        }
+        None => None
+    };
+
+    DONATIONS.idx.donation_size
+        .sub_prefix(campaign_id)
+        .range(storage, None, max, Order::Descending)
+        .take(limit)
+        .map(|donor| {
+            let ((_, donor), donation_info) = donor?;
+            Ok((donor, donation_info))
+        })
+        .collect()
+}
+
+/// Loads the donation record of a donor to a campaign, or a blank one if they've never donated to it before.
+fn load_donation_record(storage: &dyn Storage, campaign_id: u64, donor: &CanonicalAddr) -> DonationInfo {
+    // Let's see if the donor is an existing one. If they've already donated, we'll grab the record.
+    // Otherwise we are going to create a new one to save.
+    match DONATIONS.load(storage, (campaign_id, Vec::from(donor.as_slice()))) {
+        Ok(record) => {
+            record
+        }
+        Err(_) => {
+            DonationInfo {
+                donation_size: Uint256::zero(),
+                last_donation: 0,
+                cw20_donations: vec![],
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::mock_dependencies;
-    use cosmwasm_std::{CanonicalAddr, Decimal256, Uint256};
-    use crate::state::RewardPot;
+    use cosmwasm_std::{Addr, CanonicalAddr, Decimal256, HexBinary, Order, StdResult, Storage, Uint256};
+    use cw_storage_plus::Bound;
+    use crate::state::{Attestation, AttestationRequirements, ATTESTATIONS, BonusTier, Campaign, CLAIMS, ClaimRecord, donation_size_index, DonationInfo, find_attestation, save_claim_record, ClaimPolicy, DONATION_ENTRIES, DONATIONS, Payout, RewardCurve, RewardPot, REWARDED, TokenBalance, top_donors};
+
+    #[test]
+    fn test_donation_size_index_tolerates_malformed_keys() {
+        let donation_info = DonationInfo { donation_size: Uint256::from(5u128), last_donation: 0, cw20_donations: vec![] };
+
+        // A key that isn't a (campaign id, donor) pair is kept away from every real campaign instead of panicking
+        assert_eq!(donation_size_index(&[1, 2, 3], &donation_info), (u64::MAX, Uint256::from(5u128).to_be_bytes().to_vec()));
+        assert_eq!(donation_size_index(&[0, 8, 0, 0, 0, 0, 0, 0, 0, 3, 7], &donation_info), (3, Uint256::from(5u128).to_be_bytes().to_vec()));
+    }
 
     #[test]
     fn test_add_donation() {
         let mock_deps = mock_dependencies();
         let mut storage = mock_deps.storage;
-        let mut reward_pot = RewardPot {
-            total_reward_units: Default::default(),
-            GEIDs: Uint256::zero(),
-            pot_of_rewards: Uint256::zero(),
-        };
+        let mut reward_pot = RewardPot::new(0, Uint256::zero(), RewardCurve::ShareOfPot {});
 
         let donor = CanonicalAddr::from([72, 101, 108, 108, 111, 32, 119, 111, 114, 108, 100, 33]);
         let donation_time: u64 = 475200;
 
-        reward_pot.add_donation(&mut storage, donor, Uint256::from(10u128), donation_time).unwrap();
+        reward_pot.add_donation(&mut storage, donor, Uint256::from(10u128), donation_time, None).unwrap();
 
         assert_eq!(reward_pot.pot_of_rewards, Uint256::from(10u128))
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_add_cw20_donation() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(4u128), RewardCurve::ShareOfPot {});
+
+        let donor = CanonicalAddr::from([72, 101, 108, 108, 111, 32, 119, 111, 114, 108, 100, 33]);
+        let token = Addr::unchecked("cw20token");
+
+        reward_pot.add_cw20_donation(&mut storage, donor.clone(), &token, Uint256::from(10u128), 475200, None).unwrap();
+        reward_pot.add_cw20_donation(&mut storage, donor.clone(), &token, Uint256::from(30u128), 475201, None).unwrap();
+
+        let expected = vec![TokenBalance { token: token.clone(), amount: Uint256::from(40u128) }];
+        assert_eq!(reward_pot.cw20_pot_of_rewards, expected);
+        assert_eq!(reward_pot.pot_of_rewards, Uint256::zero());
+
+        let record = DONATIONS.load(&storage, (0, Vec::from(donor.as_slice()))).unwrap();
+        assert_eq!(record.cw20_donations, expected);
+        // Donating twice still makes a single donor
+        assert_eq!(reward_pot.unique_donors, 1);
+        assert_eq!(record.donation_size, Uint256::zero());
+        assert_eq!(record.last_donation, 475201);
+
+        // With 4 reward units the first claimant walks away with a quarter of every token
+        let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([1, 2, 3]), 475300, None).unwrap();
+        assert_eq!(payout.native, Uint256::zero());
+        assert_eq!(payout.cw20, vec![TokenBalance { token: token.clone(), amount: Uint256::from(10u128) }]);
+
+        reward_pot.deduct_payout(&payout);
+        assert_eq!(reward_pot.cw20_pot_of_rewards, vec![TokenBalance { token, amount: Uint256::from(30u128) }]);
+    }
+
+    #[test]
+    fn test_claims_consume_donations() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(4u128), RewardCurve::ShareOfPot {});
+
+        let early_donor = CanonicalAddr::from([1, 1, 1]);
+        let late_donor = CanonicalAddr::from([2, 2, 2]);
+
+        reward_pot.add_donation(&mut storage, early_donor.clone(), Uint256::from(400u128), 100, Some(200)).unwrap();
+
+        // The claim takes a quarter of the pot, so a quarter of the early donation
+        let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([9, 9, 9]), 150, None).unwrap();
+        reward_pot.deduct_payout(&payout);
+        assert_eq!(payout.native, Uint256::from(100u128));
+
+        // A donation made after the claim hasn't lost anything yet
+        reward_pot.add_donation(&mut storage, late_donor.clone(), Uint256::from(100u128), 160, Some(200)).unwrap();
+
+        let early_entry = DONATION_ENTRIES.load(&storage, (0, Vec::from(early_donor.as_slice()), 0)).unwrap();
+        let late_entry = DONATION_ENTRIES.load(&storage, (0, Vec::from(late_donor.as_slice()), 1)).unwrap();
+        assert_eq!(early_entry.remaining(&reward_pot), Uint256::from(300u128));
+        assert_eq!(late_entry.remaining(&reward_pot), Uint256::from(100u128));
+
+        // Not expired yet
+        assert!(!early_entry.is_withdrawable(&reward_pot, 199));
+        let withdrawn = reward_pot.withdraw_donations(&mut storage, early_donor.clone(), 199).unwrap();
+        assert_eq!(withdrawn.native, Uint256::zero());
+
+        let withdrawn = reward_pot.withdraw_donations(&mut storage, early_donor.clone(), 200).unwrap();
+        assert_eq!(withdrawn.native, Uint256::from(300u128));
+        assert_eq!(reward_pot.pot_of_rewards, Uint256::from(100u128));
+        assert!(!DONATION_ENTRIES.has(&storage, (0, Vec::from(early_donor.as_slice()), 0)));
+        assert_eq!(DONATIONS.load(&storage, (0, Vec::from(early_donor.as_slice()))).unwrap().donation_size, Uint256::from(100u128));
+    }
+
+    #[test]
+    fn test_emptied_pot_starts_a_new_epoch() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        // A single reward unit means every claim takes the whole pot
+        let mut reward_pot = RewardPot::new(0, Uint256::from(1u128), RewardCurve::ShareOfPot {});
+
+        let donor = CanonicalAddr::from([1, 1, 1]);
+        reward_pot.add_donation(&mut storage, donor.clone(), Uint256::from(50u128), 100, None).unwrap();
+
+        let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([9, 9, 9]), 150, None).unwrap();
+        reward_pot.deduct_payout(&payout);
+        assert_eq!(payout.native, Uint256::from(50u128));
+        assert_eq!(reward_pot.donation_epoch, 1);
+
+        reward_pot.add_donation(&mut storage, donor.clone(), Uint256::from(20u128), 160, None).unwrap();
+
+        let first = DONATION_ENTRIES.load(&storage, (0, Vec::from(donor.as_slice()), 0)).unwrap();
+        let second = DONATION_ENTRIES.load(&storage, (0, Vec::from(donor.as_slice()), 1)).unwrap();
+        assert_eq!(first.remaining(&reward_pot), Uint256::zero());
+        assert_eq!(second.remaining(&reward_pot), Uint256::from(20u128));
+    }
+
+    #[test]
+    fn test_even_split_curve() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(3, Uint256::from(3u128), RewardCurve::EvenSplit {});
+
+        reward_pot.add_donation(&mut storage, CanonicalAddr::from([1, 1, 1]), Uint256::from(90u128), 100, None).unwrap();
+
+        // Every one of the three reward units gets the same amount
+        for claimant in 0..3u8 {
+            let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([claimant, 9, 9]), 150, None).unwrap();
+            reward_pot.deduct_payout(&payout);
+            assert_eq!(payout.native, Uint256::from(30u128));
+        }
+
+        assert_eq!(reward_pot.pot_of_rewards, Uint256::zero());
+        assert_eq!(reward_pot.GEIDs, Uint256::from(3u128));
+        assert_eq!(reward_pot.total_paid_out.native, Uint256::from(90u128));
+        assert_eq!(reward_pot.remaining_reward_units(), Some(Uint256::zero()));
+        reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([4, 9, 9]), 150, None).unwrap_err();
+
+        // The donation entry is saved under the pot's campaign
+        assert!(DONATION_ENTRIES.has(&storage, (3, Vec::from([1, 1, 1]), 0)));
+    }
+
+    #[test]
+    fn test_campaign_policy_and_window() {
+        let campaign = Campaign {
+            name: "validators".to_string(),
+            admin: Addr::unchecked("admin"),
+            policy: ClaimPolicy {
+                accepted_quote_statuses: vec!["OK".to_string()],
+                accepted_mrenclaves: vec![HexBinary::from([7u8; 32])],
+                bonus_tiers: vec![],
+                revoked_mrenclaves: vec![],
+            },
+            start_time: Some(100),
+            end_time: Some(200),
+            donation_cutoff: Some(150),
+            treasury: None,
+        };
+
+        assert!(campaign.policy.accepts_quote_status("OK"));
+        assert!(!campaign.policy.accepts_quote_status("GROUP_OUT_OF_DATE"));
+        assert!(campaign.policy.accepts_mrenclave(&[7u8; 32]));
+        assert!(!campaign.policy.accepts_mrenclave(&[8u8; 32]));
+
+        assert!(!campaign.is_open(99));
+        assert!(campaign.is_open(100));
+        assert!(!campaign.is_open(200));
+
+        assert!(campaign.is_taking_donations(99));
+        assert!(!campaign.is_taking_donations(150));
+        assert!(!campaign.has_ended(199));
+        assert!(campaign.has_ended(200));
+
+        // An empty policy takes anything
+        let anything = ClaimPolicy::default();
+        assert!(anything.accepts_quote_status("GROUP_REVOKED"));
+        assert!(anything.accepts_mrenclave(&[0u8; 32]));
+
+        // A revoked enclave is refused whether or not it was accepted
+        let revoked = ClaimPolicy { revoked_mrenclaves: vec![HexBinary::from([7u8; 32])], ..campaign.policy };
+        assert!(!revoked.accepts_mrenclave(&[7u8; 32]));
+        let revoked = ClaimPolicy { revoked_mrenclaves: vec![HexBinary::from([0u8; 32])], ..ClaimPolicy::default() };
+        assert!(!revoked.accepts_mrenclave(&[0u8; 32]));
+        assert!(revoked.accepts_mrenclave(&[1u8; 32]));
+    }
+
+    #[test]
+    fn test_sweep_to_donors_is_pro_rata() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(2u128), RewardCurve::ShareOfPot {});
+
+        let big_donor = CanonicalAddr::from([1, 1, 1]);
+        let small_donor = CanonicalAddr::from([2, 2, 2]);
+        let token = Addr::unchecked("token");
+        reward_pot.add_donation(&mut storage, big_donor.clone(), Uint256::from(300u128), 100, None).unwrap();
+        reward_pot.add_donation(&mut storage, small_donor.clone(), Uint256::from(100u128), 100, None).unwrap();
+        reward_pot.add_cw20_donation(&mut storage, small_donor.clone(), &token, Uint256::from(60u128), 100, None).unwrap();
+
+        // A claim takes half of the pot, the sweep splits the other half
+        let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([9, 9, 9]), 150, None).unwrap();
+        reward_pot.deduct_payout(&payout);
+
+        let first_batch = reward_pot.sweep_to_donors(&storage, 1).unwrap();
+        assert_eq!(first_batch, vec![(Vec::from(big_donor.as_slice()), Payout { native: Uint256::from(150u128), cw20: vec![] })]);
+        assert!(!reward_pot.sweep.as_ref().unwrap().finished);
+
+        let second_batch = reward_pot.sweep_to_donors(&storage, 1).unwrap();
+        let small_payout = Payout { native: Uint256::from(50u128), cw20: vec![TokenBalance { token, amount: Uint256::from(30u128) }] };
+        assert_eq!(second_batch, vec![(Vec::from(small_donor.as_slice()), small_payout)]);
+        assert!(reward_pot.sweep.as_ref().unwrap().finished);
+        assert_eq!(reward_pot.pot_of_rewards, Uint256::zero());
+    }
+
+    #[test]
+    fn test_sweep_to_treasury_takes_everything() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(2u128), RewardCurve::ShareOfPot {});
+
+        reward_pot.add_donation(&mut storage, CanonicalAddr::from([1, 1, 1]), Uint256::from(300u128), 100, None).unwrap();
+
+        let leftovers = reward_pot.sweep_to_treasury();
+        assert_eq!(leftovers.native, Uint256::from(300u128));
+        assert_eq!(reward_pot.pot_of_rewards, Uint256::zero());
+        assert!(reward_pot.sweep.unwrap().finished);
+    }
+
+    #[test]
+    fn test_bonus_tiers() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(4u128), RewardCurve::ShareOfPot {});
+        reward_pot.add_donation(&mut storage, CanonicalAddr::from([1, 1, 1]), Uint256::from(400u128), 100, None).unwrap();
+
+        let policy = ClaimPolicy {
+            bonus_tiers: vec![
+                BonusTier { name: "patched".to_string(), quote_status: Some("OK".to_string()), requires_up_to_date_cpusvn: true, reward_shares: Decimal256::percent(200) },
+                BonusTier { name: "hardening".to_string(), quote_status: Some("SW_HARDENING_NEEDED".to_string()), requires_up_to_date_cpusvn: false, reward_shares: Decimal256::percent(150) },
+            ],
+            ..ClaimPolicy::default()
+        };
+
+        // An OK report from a platform behind on its CPUSVN doesn't make the first tier
+        assert_eq!(policy.bonus_tier("OK", true).unwrap().name, "patched");
+        assert!(policy.bonus_tier("OK", false).is_none());
+        assert_eq!(policy.bonus_tier("SW_HARDENING_NEEDED", false).unwrap().name, "hardening");
+        assert!(policy.bonus_tier("CONFIGURATION_NEEDED", true).is_none());
+
+        // Two shares of four units is half of the pot
+        let payout = reward_pot.claim_rewards(&mut storage, CanonicalAddr::from([9, 9, 9]), 150, policy.bonus_tier("OK", true)).unwrap();
+        assert_eq!(payout.native, Uint256::from(200u128));
+
+        // The next claimant in the same tier would get half of what is left
+        let preview = reward_pot.preview_claim(&storage, &CanonicalAddr::from([8, 9, 9]), policy.bonus_tier("OK", true)).unwrap();
+        assert_eq!(preview.native, Uint256::from(200u128));
+
+        let reward_info = REWARDED.load(&storage, (0, vec![9, 9, 9])).unwrap();
+        assert_eq!(reward_info.reward_shares, Decimal256::percent(200));
+        assert_eq!(reward_info.bonus_tier, Some("patched".to_string()));
+        assert_eq!(reward_info.received.native, Uint256::from(200u128));
+    }
+
+    #[test]
+    fn test_top_donors_follow_donation_size() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+        let mut reward_pot = RewardPot::new(0, Uint256::from(4u128), RewardCurve::ShareOfPot {});
+        let mut other_pot = RewardPot::new(1, Uint256::from(4u128), RewardCurve::ShareOfPot {});
+
+        let small = CanonicalAddr::from([1, 1, 1]);
+        let big = CanonicalAddr::from([2, 2, 2]);
+        let late = CanonicalAddr::from([3, 3, 3]);
+        reward_pot.add_donation(&mut storage, small.clone(), Uint256::from(10u128), 100, None).unwrap();
+        reward_pot.add_donation(&mut storage, big.clone(), Uint256::from(300u128), 100, Some(200)).unwrap();
+        reward_pot.add_donation(&mut storage, late.clone(), Uint256::from(20u128), 100, None).unwrap();
+        // Donations to another campaign don't show up on this leaderboard
+        other_pot.add_donation(&mut storage, small.clone(), Uint256::from(1000u128), 100, None).unwrap();
+
+        let ranking = |storage: &dyn Storage, start_after: Option<&CanonicalAddr>| -> Vec<Vec<u8>> {
+            top_donors(storage, 0, start_after, 10).unwrap().into_iter().map(|(donor, _)| donor).collect()
+        };
+        assert_eq!(ranking(&storage, None), vec![big.to_vec(), late.to_vec(), small.to_vec()]);
+        assert_eq!(ranking(&storage, Some(&big)), vec![late.to_vec(), small.to_vec()]);
+
+        // Adding to a donation moves the donor up
+        reward_pot.add_donation(&mut storage, small.clone(), Uint256::from(15u128), 150, None).unwrap();
+        assert_eq!(ranking(&storage, None), vec![big.to_vec(), small.to_vec(), late.to_vec()]);
+
+        // Withdrawing moves them down
+        reward_pot.withdraw_donations(&mut storage, big.clone(), 200).unwrap();
+        assert_eq!(ranking(&storage, None), vec![small.to_vec(), late.to_vec(), big.to_vec()]);
+    }
+
+    #[test]
+    fn test_claim_log_indexes() {
+        let mock_deps = mock_dependencies();
+        let mut storage = mock_deps.storage;
+
+        let record = |claimant: &str, group_id: u32, claim_time: u64| ClaimRecord {
+            campaign_id: 0,
+            group_id,
+            claimant: Addr::unchecked(claimant),
+            payout: Payout { native: Uint256::from(10u128), cw20: vec![] },
+            denom: "ujuno".to_string(),
+            quote_status: "OK".to_string(),
+            report_id: "1".to_string(),
+            bonus_tier: None,
+            block_height: claim_time / 5,
+            claim_time,
+        };
+        assert_eq!(save_claim_record(&mut storage, &record("alice", 1, 100)).unwrap(), 0);
+        assert_eq!(save_claim_record(&mut storage, &record("bob", 2, 150)).unwrap(), 1);
+        assert_eq!(save_claim_record(&mut storage, &record("alice", 3, 200)).unwrap(), 2);
+
+        let alices: Vec<u64> = CLAIMS.idx.claimant.prefix(Addr::unchecked("alice"))
+            .keys(&storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(alices, vec![0, 2]);
+
+        let from_150: Vec<u64> = CLAIMS.idx.claim_time
+            .keys(&storage, Some(Bound::inclusive((150, 0))), None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(from_150, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_attestation_requirements() {
+        let attestation = Attestation {
+            group_id: 0x0CB0,
+            mrenclave: HexBinary::from([0xE3; 32]),
+            quote_status: "SW_HARDENING_NEEDED".to_string(),
+            attested_at: 1_000,
+            expires_at: 2_000,
+        };
+
+        // Anything unexpired will do by default
+        let any = AttestationRequirements::default();
+        assert!(any.accepts(&attestation, 1_000));
+        assert!(any.accepts(&attestation, 1_999));
+        assert!(!any.accepts(&attestation, 2_000));
+
+        let recent = AttestationRequirements { max_age: Some(100), ..Default::default() };
+        assert!(recent.accepts(&attestation, 1_100));
+        assert!(!recent.accepts(&attestation, 1_101));
+
+        let enclave = AttestationRequirements { mrenclave: Some(HexBinary::from([0xE3; 32])), ..Default::default() };
+        assert!(enclave.accepts(&attestation, 1_500));
+        let other_enclave = AttestationRequirements { mrenclave: Some(HexBinary::from([0xAA; 32])), ..Default::default() };
+        assert!(!other_enclave.accepts(&attestation, 1_500));
+
+        let statuses = |statuses: &[&str]| AttestationRequirements { quote_statuses: statuses.iter().map(|status| status.to_string()).collect(), ..Default::default() };
+        assert!(statuses(&["OK", "SW_HARDENING_NEEDED"]).accepts(&attestation, 1_500));
+        assert!(!statuses(&["OK"]).accepts(&attestation, 1_500));
+    }
+
+    #[test]
+    fn test_find_the_latest_attestation_that_meets_the_requirements() {
+        let mut deps = mock_dependencies();
+        let alice = Addr::unchecked("alice");
+        let attestation = |mrenclave: u8, attested_at: u64| Attestation {
+            group_id: 0x0CB0,
+            mrenclave: HexBinary::from([mrenclave; 32]),
+            quote_status: "OK".to_string(),
+            attested_at,
+            expires_at: attested_at + 1_000,
+        };
+        ATTESTATIONS.save(deps.as_mut().storage, (&alice, [0xAA; 32].as_slice()), &attestation(0xAA, 500)).unwrap();
+        ATTESTATIONS.save(deps.as_mut().storage, (&alice, [0xBB; 32].as_slice()), &attestation(0xBB, 100)).unwrap();
+
+        let any = AttestationRequirements::default();
+        assert_eq!(find_attestation(&deps.storage, &alice, &any, 600).unwrap(), Some(attestation(0xAA, 500)));
+        // Older attestations still count for requirements the newer ones don't meet
+        let enclave = AttestationRequirements { mrenclave: Some(HexBinary::from([0xBB; 32])), ..Default::default() };
+        assert_eq!(find_attestation(&deps.storage, &alice, &enclave, 600).unwrap(), Some(attestation(0xBB, 100)));
+        assert_eq!(find_attestation(&deps.storage, &alice, &enclave, 1_100).unwrap(), None);
+        assert_eq!(find_attestation(&deps.storage, &Addr::unchecked("bob"), &any, 600).unwrap(), None);
+    }
+}
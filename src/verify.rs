@@ -0,0 +1,335 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Binary, ensure, ensure_eq, HexBinary, StdError, StdResult};
+use sha2::{Digest, Sha256};
+
+use crate::intelstructs::{ClaimStruct, IAS_REPORT_SIGNING_EXPONENT, IAS_REPORT_SIGNING_MODULUS, IASReport, ReportBody, verify_pkcs1v15_sha256};
+use crate::platforminfoblob::{convert_claim_struct_to_payload, convert_platform_info_hex, get_mrenclave_from_quote_body, get_payload_from_quote_body, platform_info_blob};
+use crate::ContractError;
+use crate::state::ClaimPolicy;
+
+/// The outcome of a single check that a report goes through before we pay out on it
+#[cw_serde]
+pub struct ReportCheck {
+    /// What was checked
+    pub check: String,
+    pub passed: bool,
+    /// Why the check failed, unset when it passed
+    pub reason: Option<String>
+}
+
+/// Everything we learnt about a report by checking it against a campaign's policy
+pub struct ReportVerification {
+    /// Every check the report went through, in the order they are run when claiming
+    pub checks: Vec<ReportCheck>,
+    /// The report's platform info blob, when it could be parsed
+    pub platform_info: Option<platform_info_blob>,
+    /// Why the first failed check failed
+    failure: Option<ContractError>
+}
+
+impl ReportVerification {
+    /// The first check the report failed, if any
+    pub fn first_failure(&self) -> Option<&ReportCheck> {
+        self.checks.iter().find(|check| !check.passed)
+    }
+
+    /// Records the outcome of a check under `check`
+    pub fn record<T, E: Into<ContractError>>(&mut self, check: &str, outcome: Result<T, E>) -> Option<T> {
+        match outcome {
+            Ok(value) => {
+                self.checks.push(ReportCheck { check: check.to_string(), passed: true, reason: None });
+                Some(value)
+            }
+            Err(error) => {
+                let error = error.into();
+                self.checks.push(ReportCheck { check: check.to_string(), passed: false, reason: Some(reason(&error)) });
+                self.failure.get_or_insert(error);
+                None
+            }
+        }
+    }
+
+    /// The report's platform info blob when every check passed, otherwise the first failed check as an error, so
+    /// claiming fails the same way a dry run reports it
+    pub fn into_platform_info(self) -> Result<platform_info_blob, ContractError> {
+        if let Some(failure) = self.failure {
+            return Err(failure);
+        }
+
+        self.platform_info.ok_or(ContractError::InvalidPlatformInfoBlob {})
+    }
+}
+
+/// Generic errors are only a message, there is no need for the "Generic error: " in front of it
+fn reason(error: &ContractError) -> String {
+    match error {
+        ContractError::Std(StdError::GenericErr { msg, .. }) => msg.clone(),
+        other => other.to_string()
+    }
+}
+
+/// Runs every check on the report itself: whether the campaign's policy accepts it, whether it carries the claim
+/// and whether Intel signed it. Checks that depend on the campaign's state, like whether the group id has been
+/// seen before, are up to the caller.
+pub fn verify_report(report: &IASReport, claim_struct: &ClaimStruct, policy: &ClaimPolicy) -> ReportVerification {
+    verify_report_with_key(report, claim_struct, policy, IAS_REPORT_SIGNING_MODULUS, IAS_REPORT_SIGNING_EXPONENT)
+}
+
+/// Runs the same checks as `verify_report`, with the signature checked against the RSA key with the given modulus
+/// and exponent
+pub fn verify_report_with_key(report: &IASReport, claim_struct: &ClaimStruct, policy: &ClaimPolicy, modulus: &[u8], exponent: &[u8]) -> ReportVerification {
+    let mut verification = ReportVerification { checks: vec![], platform_info: None, failure: None };
+
+    // The report has to have a quote status the campaign accepts
+    let quote_status = &report.report.isv_enclave_quote_status;
+    verification.record("quote_status", if policy.accepts_quote_status(quote_status) {
+        Ok(())
+    } else {
+        Err(ContractError::QuoteStatusNotAccepted { status: quote_status.clone() })
+    });
+
+    // Convert the platform info blob from hex to struct, it holds the group id
+    let platform_info = convert_platform_info_hex(&report.report.platform_info_blob)
+        .map_err(|_| ContractError::InvalidPlatformInfoBlob {});
+    verification.platform_info = verification.record("platform_info_blob", platform_info);
+
+    // Load the ISV enclave quote body, encoded as base64
+    let quote_body = Binary::from_base64(&report.report.isv_enclave_quote_body).ok();
+    let quote_body = || quote_body.as_ref().ok_or_else(|| ContractError::InvalidBase64 { field: "isvEnclaveQuoteBody".to_string() });
+
+    // The report has to come from an enclave the campaign accepts
+    verification.record("mrenclave", quote_body().and_then(|quote_body| check_mrenclave(quote_body, policy)));
+
+    // The claim struct generated by the enclave is stored in the payload field, it has to be the one that was passed in
+    verification.record("payload", quote_body().and_then(|quote_body| check_payload(quote_body, claim_struct)));
+
+    // The report has to be signed by Intel
+    verification.record("signature", check_signature(report, modulus, exponent));
+
+    verification
+}
+
+fn check_mrenclave(quote_body: &[u8], policy: &ClaimPolicy) -> Result<(), ContractError> {
+    let mrenclave = get_mrenclave_from_quote_body(quote_body)
+        .map_err(|_| ContractError::InvalidQuoteBody { part: "MRENCLAVE".to_string() })?;
+    ensure!(policy.accepts_mrenclave(&mrenclave), ContractError::MrenclaveNotAccepted { mrenclave: HexBinary::from(mrenclave).to_hex() });
+
+    Ok(())
+}
+
+fn check_payload(quote_body: &[u8], claim_struct: &ClaimStruct) -> Result<(), ContractError> {
+    // Extract the payload from the quote body
+    let payload = get_payload_from_quote_body(quote_body)
+        .map_err(|_| ContractError::InvalidQuoteBody { part: "payload".to_string() })?;
+
+    // Convert the claim struct to the stringified claim struct
+    let hashed_claim_struct = convert_claim_struct_to_payload(claim_struct.clone());
+
+    ensure_eq!(payload, hashed_claim_struct, ContractError::PayloadMismatch {});
+
+    Ok(())
+}
+
+/// What the signature of a report is over: the report as JSON, without the backslashes that escape stuff
+pub fn signed_report_json(report: &ReportBody) -> StdResult<String> {
+    Ok(serde_json::to_string(report)
+        .map_err(|error| StdError::generic_err(format!("The report can't be serialized: {}", error)))?
+        .replace('\\', ""))
+}
+
+fn check_signature(report: &IASReport, modulus: &[u8], exponent: &[u8]) -> Result<(), ContractError> {
+    // Convert the ias report as a json string, removing all the backslashes to escape stuff
+    let ias_report_asjson = signed_report_json(&report.report)?;
+
+    // Convert the base64 ias report signature into bytes
+    let ias_report_sig = Binary::from_base64(&report.report_sig)
+        .map_err(|_| ContractError::InvalidBase64 { field: "reportsig".to_string() })?;
+
+    // We are going to calculate our own digest of the ias report
+    let sha256d_report = Sha256::digest(ias_report_asjson.as_bytes());
+
+    // ensure that the signature is the signing key's signature of that digest, which can't be without a modulus
+    ensure!(modulus.iter().any(|byte| *byte != 0), StdError::generic_err("The signing key has no modulus"));
+    ensure!(verify_pkcs1v15_sha256(ias_report_sig.as_slice(), exponent, modulus, &sha256d_report), ContractError::SignatureMismatch {});
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Binary, HexBinary};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use crate::ContractError;
+    use crate::fixtures::{CLAIM, IAS_RESPONSE};
+    use crate::intelstructs::{ClaimStruct, IASReport};
+    use crate::payload::GOLDEN_VECTORS;
+    use crate::state::ClaimPolicy;
+    use crate::mock_ias::{MockIas, MockPlatform};
+    use crate::verify::{check_payload, check_signature, verify_report, verify_report_with_key};
+
+
+    #[test]
+    fn test_verify_report_lists_every_check() {
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let claim: ClaimStruct = serde_json::from_str(CLAIM).unwrap();
+        let policy = ClaimPolicy { accepted_quote_statuses: vec!["OK".to_string()], ..ClaimPolicy::default() };
+
+        let verification = verify_report(&report, &claim, &policy);
+
+        let checks: Vec<(&str, bool)> = verification.checks.iter().map(|check| (check.check.as_str(), check.passed)).collect();
+        assert_eq!(checks[0], ("quote_status", false));
+        assert_eq!(checks[1], ("platform_info_blob", true));
+        assert_eq!(checks[2], ("mrenclave", true));
+        assert_eq!(checks.len(), 5);
+        assert!(verification.platform_info.is_some());
+
+        let failure = verification.into_platform_info().unwrap_err();
+        assert_eq!(failure, ContractError::QuoteStatusNotAccepted { status: "CONFIGURATION_AND_SW_HARDENING_NEEDED".to_string() });
+    }
+
+    #[test]
+    fn test_captured_report_passes() {
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let claim: ClaimStruct = serde_json::from_str(CLAIM).unwrap();
+
+        // Signed by Intel's report signing key, which is what a claim is checked against
+        let verification = verify_report(&report, &claim, &ClaimPolicy::default());
+        assert!(verification.checks.iter().all(|check| check.passed), "{:?}", verification.checks);
+        assert_eq!(verification.into_platform_info().unwrap().group_id(), 0x0CB0);
+    }
+
+    #[test]
+    fn test_verify_report_survives_garbage() {
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.platform_info_blob = "not hex".to_string();
+        report.report.isv_enclave_quote_body = "AAAA".to_string();
+        report.report_sig = "%%%".to_string();
+        let claim: ClaimStruct = serde_json::from_str(CLAIM).unwrap();
+
+        let verification = verify_report(&report, &claim, &ClaimPolicy::default());
+
+        assert!(verification.checks.iter().skip(1).all(|check| !check.passed && check.reason.is_some()));
+        assert!(verification.platform_info.is_none());
+        assert_eq!(verification.into_platform_info().unwrap_err(), ContractError::InvalidPlatformInfoBlob {});
+    }
+
+    #[test]
+    fn test_verify_report_errors() {
+        let claim: ClaimStruct = serde_json::from_str(CLAIM).unwrap();
+        let first_failure = |report: &IASReport, claim: &ClaimStruct| verify_report(report, claim, &ClaimPolicy::default()).into_platform_info().unwrap_err();
+
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.isv_enclave_quote_body = "not base64!".to_string();
+        assert_eq!(first_failure(&report, &claim), ContractError::InvalidBase64 { field: "isvEnclaveQuoteBody".to_string() });
+
+        // Letters past f are alphanumeric but aren't hex
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.platform_info_blob = "z".repeat(210);
+        assert_eq!(first_failure(&report, &claim), ContractError::InvalidPlatformInfoBlob {});
+
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report.isv_enclave_quote_body = "AAAA".to_string();
+        assert_eq!(first_failure(&report, &claim), ContractError::InvalidQuoteBody { part: "MRENCLAVE".to_string() });
+
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let other_claim: ClaimStruct = serde_json::from_str(r#"{"address":[1,2,3],"message":"Hello world!"}"#).unwrap();
+        assert_eq!(first_failure(&report, &other_claim), ContractError::PayloadMismatch {});
+
+        // Anything that was changed after Intel signed it
+        let mut tampered = report.clone();
+        tampered.report.advisory_ids.pop();
+        assert_eq!(first_failure(&tampered, &claim), ContractError::SignatureMismatch {});
+
+        let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        report.report_sig = "%%%".to_string();
+        assert_eq!(first_failure(&report, &claim), ContractError::InvalidBase64 { field: "reportsig".to_string() });
+
+        let policy = ClaimPolicy { accepted_mrenclaves: vec![HexBinary::from([0u8; 32])], ..ClaimPolicy::default() };
+        let error = verify_report(&report, &claim, &policy).into_platform_info().unwrap_err();
+        assert!(matches!(error, ContractError::MrenclaveNotAccepted { .. }));
+    }
+
+    #[test]
+    fn test_payload_check_golden_vectors() {
+        let report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+        let payload_check = |claim_json: &str, report_data: &str| {
+            let claim: ClaimStruct = serde_json::from_str(claim_json).unwrap();
+            check_payload(&[vec![0u8; 368], hex::decode(report_data).unwrap()].concat(), &claim)
+        };
+
+        // The claims the payload builder hands out are the ones the contract accepts with its report data
+        for (_, _, claim_json, report_data) in GOLDEN_VECTORS {
+            assert_eq!(payload_check(claim_json, report_data), Ok(()));
+        }
+        let (_, _, claim_json, _) = GOLDEN_VECTORS[1];
+        let (_, _, _, other_report_data) = GOLDEN_VECTORS[2];
+        assert_eq!(payload_check(claim_json, other_report_data), Err(ContractError::PayloadMismatch {}));
+
+        // The captured report carries the first one
+        let (_, _, claim_json, _) = GOLDEN_VECTORS[0];
+        let claim: ClaimStruct = serde_json::from_str(claim_json).unwrap();
+        assert!(verify_report(&report, &claim, &ClaimPolicy::default()).checks.iter().any(|check| check.check == "payload" && check.passed));
+    }
+
+    fn any_report() -> impl Strategy<Value = IASReport> {
+        // Strings that are anything, or that get past the base64 and hex decoding to the parsers behind it
+        let base64 = || prop_oneof![any::<String>(), vec(any::<u8>(), 0..500).prop_map(|bytes| Binary::from(bytes).to_base64())];
+        let pib = prop_oneof![any::<String>(), vec(any::<u8>(), 100..110).prop_map(hex::encode)];
+
+        (any::<String>(), any::<String>(), base64(), pib, base64(), base64()).prop_map(|(id, status, pseudonym, pib, quote_body, signature)| {
+            let mut report: IASReport = serde_json::from_str(IAS_RESPONSE).unwrap();
+            report.report.id = id;
+            report.report.isv_enclave_quote_status = status;
+            report.report.epid_pseudonym = Binary::from_base64(&pseudonym).unwrap_or_default();
+            report.report.platform_info_blob = pib;
+            report.report.isv_enclave_quote_body = quote_body;
+            report.report_sig = signature;
+            report
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ias_json_never_panics(json in "\\PC{0,400}") {
+            let _ = serde_json::from_str::<IASReport>(&json);
+        }
+
+        #[test]
+        fn prop_ias_json_with_garbage_fields_never_panics(field in "\\PC{0,100}", value in "\\PC{0,100}") {
+            let mut report: serde_json::Value = serde_json::from_str(IAS_RESPONSE).unwrap();
+            report["report"][field] = serde_json::Value::String(value);
+            if let Ok(report) = serde_json::from_value::<IASReport>(report) {
+                let _ = verify_report(&report, &serde_json::from_str(CLAIM).unwrap(), &ClaimPolicy::default());
+            }
+        }
+
+        #[test]
+        fn prop_base64_never_panics(encoded in "\\PC{0,200}") {
+            let _ = Binary::from_base64(&encoded);
+        }
+
+        #[test]
+        fn prop_garbage_reports_are_refused(report in any_report(), claim_json in prop_oneof![Just(CLAIM.to_string()), Just(r#"{"address":[],"message":""}"#.to_string())]) {
+            let claim: ClaimStruct = serde_json::from_str(&claim_json).unwrap();
+            prop_assert!(verify_report(&report, &claim, &ClaimPolicy::default()).into_platform_info().is_err());
+
+            let key = MockIas::default().signing_key();
+            prop_assert!(verify_report_with_key(&report, &claim, &ClaimPolicy::default(), &key.modulus, &key.exponent).into_platform_info().is_err());
+        }
+
+        #[test]
+        fn prop_any_change_to_a_signed_report_breaks_the_signature(byte in 0usize..128, bit in 0u8..8) {
+            let ias = MockIas::default();
+            let key = ias.signing_key();
+            let report = ias.report(&MockPlatform::default());
+            prop_assert_eq!(check_signature(&report, &key.modulus, &key.exponent), Ok(()));
+
+            let mut signature = Binary::from_base64(&report.report_sig).unwrap().to_vec();
+            signature[byte] ^= 1 << bit;
+            let tampered = IASReport { report_sig: Binary::from(signature).to_base64(), ..report };
+            prop_assert_eq!(check_signature(&tampered, &key.modulus, &key.exponent), Err(ContractError::SignatureMismatch {}));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use cosmwasm_std::{Addr, Binary, StdError, StdResult, Storage};
+use cw_storage_plus::Map;
+use sha2::{Digest, Sha256};
+
+/// The hash of every viewing key that was set, the keys themselves are never stored. Address => SHA-256 of the key
+pub static VIEWING_KEYS: Map<&Addr, [u8; 32]> = Map::new("viewing_keys");
+
+/// Private query responses are padded to a multiple of this, so their length doesn't tell how many claims there are
+pub const QUERY_BLOCK_SIZE: usize = 256;
+
+/// The same error whether no key was set or the key is wrong, so the error doesn't tell whether an address claimed
+pub fn wrong_viewing_key() -> StdError {
+    StdError::generic_err("Wrong viewing key for this address or viewing key not set")
+}
+
+pub fn set_viewing_key(storage: &mut dyn Storage, address: &Addr, key: &str) -> StdResult<()> {
+    VIEWING_KEYS.save(storage, address, &Sha256::digest(key.as_bytes()).into())
+}
+
+/// Whether `key` is the viewing key `address` set
+pub fn check_viewing_key(storage: &dyn Storage, address: &Addr, key: &str) -> StdResult<()> {
+    let hashed_key: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+
+    match VIEWING_KEYS.may_load(storage, address)? {
+        Some(stored) if constant_time_eq(&stored, &hashed_key) => Ok(()),
+        _ => Err(wrong_viewing_key()),
+    }
+}
+
+/// Compares every byte whatever the first difference is, so the time it takes doesn't leak the key's hash
+fn constant_time_eq(left: &[u8; 32], right: &[u8; 32]) -> bool {
+    left.iter().zip(right.iter()).fold(0u8, |difference, (left, right)| difference | (left ^ right)) == 0
+}
+
+/// Pads a private query's response with spaces up to the next multiple of `QUERY_BLOCK_SIZE`, JSON ignores them
+pub fn pad_query_response(response: Binary) -> Binary {
+    let mut response = response.0;
+    let missing = (QUERY_BLOCK_SIZE - response.len() % QUERY_BLOCK_SIZE) % QUERY_BLOCK_SIZE;
+    response.resize(response.len() + missing, b' ');
+
+    Binary(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Binary, from_json};
+    use cosmwasm_std::testing::MockStorage;
+
+    use crate::viewing_keys::{check_viewing_key, pad_query_response, QUERY_BLOCK_SIZE, set_viewing_key, wrong_viewing_key};
+
+    #[test]
+    fn test_viewing_keys() {
+        let mut storage = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+
+        // Nobody has a key until they set one
+        assert_eq!(check_viewing_key(&storage, &alice, "").unwrap_err(), wrong_viewing_key());
+
+        set_viewing_key(&mut storage, &alice, "hunter2").unwrap();
+        check_viewing_key(&storage, &alice, "hunter2").unwrap();
+        assert_eq!(check_viewing_key(&storage, &alice, "hunter3").unwrap_err(), wrong_viewing_key());
+        assert_eq!(check_viewing_key(&storage, &Addr::unchecked("bob"), "hunter2").unwrap_err(), wrong_viewing_key());
+
+        // Setting a key again replaces the old one
+        set_viewing_key(&mut storage, &alice, "correct horse").unwrap();
+        check_viewing_key(&storage, &alice, "correct horse").unwrap();
+        assert!(check_viewing_key(&storage, &alice, "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_padded_responses_still_parse() {
+        let padded = pad_query_response(Binary::from(br#"{"claims":[]}"#.as_slice()));
+
+        assert_eq!(padded.len(), QUERY_BLOCK_SIZE);
+        assert_eq!(from_json::<serde_json::Value>(&padded).unwrap(), serde_json::json!({ "claims": [] }));
+
+        // A response that fills its blocks isn't padded any further
+        let full = Binary::from(vec![b' '; QUERY_BLOCK_SIZE * 2]);
+        assert_eq!(pad_query_response(full.clone()), full);
+        assert_eq!(pad_query_response(Binary::from(vec![b'1'; QUERY_BLOCK_SIZE + 1])).len(), QUERY_BLOCK_SIZE * 2);
+    }
+}